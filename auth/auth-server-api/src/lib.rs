@@ -6,24 +6,38 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![feature(trivial_bounds)]
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// The Renegade API key header
 pub const RENEGADE_API_KEY_HEADER: &str = "X-Renegade-Api-Key";
+/// The header on match responses carrying the id assigned to the bundle
+///
+/// The same id is sent in the settlement webhook for the bundle
+pub const RENEGADE_BUNDLE_ID_HEADER: &str = "X-Renegade-Bundle-Id";
 
 // ----------------------
 // | API Key Management |
 // ----------------------
 
-/// The path to create a new API key
+/// The path to create a new API key or list existing API keys
 ///
 /// POST /api-keys
+/// GET /api-keys
 pub const API_KEYS_PATH: &str = "api-keys";
 /// The path to mark an API key as inactive
 ///
 /// POST /api-keys/{id}/deactivate
 pub const DEACTIVATE_API_KEY_PATH: &str = "/api-keys/{id}/deactivate";
+/// The path to set the settlement webhook for an API key
+///
+/// POST /api-keys/{id}/settlement-webhook
+pub const SET_SETTLEMENT_WEBHOOK_PATH: &str = "/api-keys/{id}/settlement-webhook";
+/// The path to fetch usage statistics for an API key
+///
+/// GET /api-keys/{id}/usage?window_minutes={window_minutes}
+pub const API_KEY_USAGE_PATH: &str = "/api-keys/{id}/usage";
 
 /// A request to create a new API key
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,4 +50,81 @@ pub struct CreateApiKeyRequest {
     pub secret: String,
     /// A description of the API key's purpose
     pub description: String,
+    /// The time at which the API key expires
+    ///
+    /// If `None`, the key does not expire
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The metadata of an API key, excluding its secret
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyMetadata {
+    /// The API key id
+    pub id: Uuid,
+    /// A description of the API key's purpose
+    pub description: String,
+    /// Whether the API key is active
+    pub is_active: bool,
+    /// The time at which the API key was created
+    pub created_at: DateTime<Utc>,
+    /// The time at which the API key expires, if any
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The URL to which settlement webhooks are sent, if any
+    pub settlement_webhook_url: Option<String>,
+}
+
+/// A response listing all API keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListApiKeysResponse {
+    /// The API keys
+    pub keys: Vec<ApiKeyMetadata>,
+}
+
+/// A response containing the usage statistics of an API key over a window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyUsageResponse {
+    /// The API key id
+    pub id: Uuid,
+    /// The window in minutes over which usage was counted
+    pub window_minutes: u64,
+    /// The number of external quote requests
+    pub quote_requests: u64,
+    /// The number of quote assembly and direct match requests
+    pub assemble_requests: u64,
+    /// The number of bundles that settled on-chain
+    pub successful_settlements: u64,
+}
+
+/// A request to set the settlement webhook URL for an API key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetSettlementWebhookRequest {
+    /// The URL to which settlement webhooks are sent
+    ///
+    /// If `None`, the key's settlement webhook is removed
+    pub url: Option<String>,
+}
+
+/// The payload POSTed to an API key's settlement webhook once a bundle
+/// forwarded to that key settles or fails to settle in time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettlementWebhookPayload {
+    /// The id of the bundle
+    pub bundle_id: Uuid,
+    /// The description of the API key that requested the bundle
+    pub key_description: String,
+    /// Whether the bundle settled on-chain
+    pub settled: bool,
+    /// The hash of the settlement transaction
+    ///
+    /// `None` if settlement was not observed before the timeout
+    pub tx_hash: Option<String>,
+    /// The mint of the base token in the match
+    pub base_mint: String,
+    /// The mint of the quote token in the match
+    pub quote_mint: String,
+    /// The matched amount of the base token
+    pub base_amount: u128,
+    /// The matched amount of the quote token
+    pub quote_amount: u128,
 }
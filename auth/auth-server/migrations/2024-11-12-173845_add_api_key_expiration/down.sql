@@ -0,0 +1,2 @@
+-- Remove the expiration time from API keys
+ALTER TABLE api_keys DROP COLUMN expires_at;
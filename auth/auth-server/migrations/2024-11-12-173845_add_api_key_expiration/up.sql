@@ -0,0 +1,2 @@
+-- Add an optional expiration time to API keys
+ALTER TABLE api_keys ADD COLUMN expires_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Remove the settlement webhook URL from API keys
+ALTER TABLE api_keys DROP COLUMN settlement_webhook_url;
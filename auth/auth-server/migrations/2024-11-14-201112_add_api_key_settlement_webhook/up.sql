@@ -0,0 +1,2 @@
+-- Add an optional settlement webhook URL to API keys
+ALTER TABLE api_keys ADD COLUMN settlement_webhook_url VARCHAR;
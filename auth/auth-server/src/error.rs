@@ -10,6 +10,9 @@ pub enum AuthServerError {
     /// API key inactive
     #[error("API key inactive")]
     ApiKeyInactive,
+    /// API key expired
+    #[error("API key expired")]
+    ApiKeyExpired,
     /// Database connection error
     #[error("Database connection error: {0}")]
     DatabaseConnection(String),
@@ -28,6 +31,10 @@ pub enum AuthServerError {
     /// Unauthorized
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    /// A request that is invalid given the stored state, e.g. one that
+    /// references a nonexistent key
+    #[error("Bad request: {0}")]
+    BadRequest(String),
 }
 
 impl AuthServerError {
@@ -66,6 +73,12 @@ impl AuthServerError {
     pub fn unauthorized<T: ToString>(msg: T) -> Self {
         Self::Unauthorized(msg.to_string())
     }
+
+    /// Create a new bad request error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn bad_request<T: ToString>(msg: T) -> Self {
+        Self::BadRequest(msg.to_string())
+    }
 }
 
 impl warp::reject::Reject for AuthServerError {}
@@ -76,6 +89,8 @@ impl From<AuthServerError> for ApiError {
             AuthServerError::ApiKeyInactive | AuthServerError::Unauthorized(_) => {
                 ApiError::Unauthorized
             },
+            AuthServerError::ApiKeyExpired => ApiError::ApiKeyExpired,
+            AuthServerError::BadRequest(msg) => ApiError::BadRequest(msg),
             _ => ApiError::InternalError(err.to_string()),
         }
     }
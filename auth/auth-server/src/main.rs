@@ -40,10 +40,24 @@ use tracing::{error, info};
 use uuid::Uuid;
 use warp::{Filter, Rejection, Reply};
 
-use server::Server;
+use server::{KeyUsageQuery, Server};
 
 /// The default internal server error message
 const DEFAULT_INTERNAL_SERVER_ERROR_MESSAGE: &str = "Internal Server Error";
+/// The error code returned for internal server errors
+const INTERNAL_ERROR_CODE: &str = "INTERNAL_ERROR";
+/// The error code returned for malformed or invalid requests
+const BAD_REQUEST_ERROR_CODE: &str = "BAD_REQUEST";
+/// The error code returned when a rate limit is exceeded
+const RATE_LIMITED_ERROR_CODE: &str = "RATE_LIMITED";
+/// The error code returned for unauthorized requests
+const UNAUTHORIZED_ERROR_CODE: &str = "UNAUTHORIZED";
+/// The error code returned for requests authorized with an expired API key
+const API_KEY_EXPIRED_ERROR_CODE: &str = "API_KEY_EXPIRED";
+/// The error code returned when the relayer does not respond in time
+const GATEWAY_TIMEOUT_ERROR_CODE: &str = "GATEWAY_TIMEOUT";
+/// The error code returned for unknown routes
+const NOT_FOUND_ERROR_CODE: &str = "NOT_FOUND";
 /// The dummy private key used to instantiate the arbitrum client
 ///
 /// We don't need any client functionality using a real private key, so instead
@@ -87,6 +101,21 @@ pub struct Cli {
     /// The bundle rate limit in bundles per minute
     #[arg(long, env = "BUNDLE_RATE_LIMIT", default_value = "4")]
     pub bundle_rate_limit: u64,
+    /// The maximum age, in milliseconds, of a signed quote submitted for
+    /// assembly
+    ///
+    /// Older quotes are rejected before being forwarded to the relayer
+    #[arg(long, env = "MAX_QUOTE_AGE_MS", default_value = "10000")]
+    pub max_quote_age_ms: u64,
+    /// The timeout, in milliseconds, for requests proxied to the relayer
+    #[arg(long, env = "RELAYER_TIMEOUT_MS", default_value = "10000")]
+    pub relayer_timeout_ms: u64,
+    /// A comma-separated list of base tokens (tickers or addresses) that
+    /// external matches may be requested on
+    ///
+    /// If unset, all base tokens are allowed
+    #[arg(long, env = "BASE_TOKEN_ALLOWLIST", value_delimiter = ',')]
+    pub base_token_allowlist: Vec<String>,
     /// The path to the file containing token remaps for the given chain
     ///
     /// See https://github.com/renegade-fi/token-mappings for more information on the format of this file
@@ -135,6 +164,12 @@ pub enum ApiError {
     /// An unauthorized error
     #[error("Unauthorized")]
     Unauthorized,
+    /// An unauthorized error due to an expired API key
+    #[error("API key expired")]
+    ApiKeyExpired,
+    /// The relayer did not respond in time
+    #[error("Relayer request timed out")]
+    GatewayTimeout,
 }
 
 impl ApiError {
@@ -149,6 +184,18 @@ impl ApiError {
     pub fn bad_request<T: ToString>(msg: T) -> Self {
         Self::BadRequest(msg.to_string())
     }
+
+    /// Get the stable, machine-readable code for the error
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::InternalError(_) => INTERNAL_ERROR_CODE,
+            Self::BadRequest(_) => BAD_REQUEST_ERROR_CODE,
+            Self::TooManyRequests => RATE_LIMITED_ERROR_CODE,
+            Self::Unauthorized => UNAUTHORIZED_ERROR_CODE,
+            Self::ApiKeyExpired => API_KEY_EXPIRED_ERROR_CODE,
+            Self::GatewayTimeout => GATEWAY_TIMEOUT_ERROR_CODE,
+        }
+    }
 }
 
 // Implement warp::reject::Reject for ApiError
@@ -210,6 +257,18 @@ async fn main() {
         .and(warp::get())
         .map(|| warp::reply::with_status("PONG", StatusCode::OK));
 
+    // List all API keys
+    let list_api_keys = warp::path(API_KEYS_PATH)
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::path::full())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and(with_server(server.clone()))
+        .and_then(|path, headers, body, server: Arc<Server>| async move {
+            server.list_keys(path, headers, body).await
+        });
+
     // Add an API key
     let add_api_key = warp::path(API_KEYS_PATH)
         .and(warp::post())
@@ -234,6 +293,35 @@ async fn main() {
             server.expire_key(id, path, headers, body).await
         });
 
+    // Set the settlement webhook for an API key
+    let set_settlement_webhook = warp::path(API_KEYS_PATH)
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("settlement-webhook"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::path::full())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and(with_server(server.clone()))
+        .and_then(|id, path, headers, body, server: Arc<Server>| async move {
+            server.set_settlement_webhook(id, path, headers, body).await
+        });
+
+    // Get the usage statistics of an API key
+    let api_key_usage = warp::path(API_KEYS_PATH)
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("usage"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<KeyUsageQuery>())
+        .and(warp::path::full())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and(with_server(server.clone()))
+        .and_then(|id, query, path, headers, body, server: Arc<Server>| async move {
+            server.get_key_usage(id, query, path, headers, body).await
+        });
+
     // --- Proxied Routes --- //
 
     let external_quote_path = warp::path("v0")
@@ -279,6 +367,9 @@ async fn main() {
         .or(external_quote_path)
         .or(external_quote_assembly_path)
         .or(expire_api_key)
+        .or(set_settlement_webhook)
+        .or(api_key_usage)
+        .or(list_api_keys)
         .or(add_api_key)
         .recover(handle_rejection);
     warp::serve(routes).bind(listen_addr).await;
@@ -302,14 +393,22 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
             ApiError::TooManyRequests => (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded"),
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            ApiError::ApiKeyExpired => (StatusCode::UNAUTHORIZED, "API key expired"),
+            ApiError::GatewayTimeout => {
+                (StatusCode::GATEWAY_TIMEOUT, "Timed out waiting for the relayer")
+            },
         };
 
-        Ok(json_error(message, code))
+        Ok(json_error(api_error.error_code(), message, code))
     } else if err.is_not_found() {
-        Ok(json_error("Not Found", StatusCode::NOT_FOUND))
+        Ok(json_error(NOT_FOUND_ERROR_CODE, "Not Found", StatusCode::NOT_FOUND))
     } else {
         error!("unhandled rejection: {:?}", err);
-        Ok(json_error("Internal Server Error", StatusCode::INTERNAL_SERVER_ERROR))
+        Ok(json_error(
+            INTERNAL_ERROR_CODE,
+            DEFAULT_INTERNAL_SERVER_ERROR_MESSAGE,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))
     }
 }
 
@@ -317,8 +416,10 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
 // | Helpers |
 // -----------
 
-/// Return a json error from a string message
-fn json_error(msg: &str, code: StatusCode) -> impl Reply {
-    let json = json!({ "error": msg });
+/// Return a json error from an error code and message
+///
+/// The body is of the form `{ "error": { "code": ..., "message": ... } }`
+fn json_error(error_code: &str, msg: &str, code: StatusCode) -> impl Reply {
+    let json = json!({ "error": { "code": error_code, "message": msg } });
     warp::reply::with_status(warp::reply::json(&json), code)
 }
@@ -5,6 +5,8 @@
 use std::time::SystemTime;
 
 use crate::schema::api_keys;
+use auth_server_api::ApiKeyMetadata;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -15,9 +17,30 @@ pub struct ApiKey {
     pub id: Uuid,
     pub encrypted_key: String,
     pub description: String,
-    #[allow(dead_code)]
     pub created_at: SystemTime,
     pub is_active: bool,
+    pub expires_at: Option<SystemTime>,
+    pub settlement_webhook_url: Option<String>,
+}
+
+impl ApiKey {
+    /// Whether the key has passed its expiration time
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= SystemTime::now())
+    }
+}
+
+impl From<ApiKey> for ApiKeyMetadata {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            description: key.description,
+            is_active: key.is_active,
+            created_at: DateTime::<Utc>::from(key.created_at),
+            expires_at: key.expires_at.map(DateTime::<Utc>::from),
+            settlement_webhook_url: key.settlement_webhook_url,
+        }
+    }
 }
 
 #[derive(Insertable)]
@@ -26,12 +49,18 @@ pub struct NewApiKey {
     pub id: Uuid,
     pub encrypted_key: String,
     pub description: String,
+    pub expires_at: Option<SystemTime>,
 }
 
 impl NewApiKey {
     /// Create a new API key
-    pub fn new(id: Uuid, encrypted_key: String, description: String) -> Self {
-        Self { id, encrypted_key, description }
+    pub fn new(
+        id: Uuid,
+        encrypted_key: String,
+        description: String,
+        expires_at: Option<SystemTime>,
+    ) -> Self {
+        Self { id, encrypted_key, description, expires_at }
     }
 }
 
@@ -43,6 +72,8 @@ impl From<NewApiKey> for ApiKey {
             description: key.description,
             created_at: SystemTime::now(),
             is_active: true,
+            expires_at: key.expires_at,
+            settlement_webhook_url: None,
         }
     }
 }
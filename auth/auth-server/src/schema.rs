@@ -7,5 +7,7 @@ diesel::table! {
         description -> Varchar,
         created_at -> Timestamp,
         is_active -> Bool,
+        expires_at -> Nullable<Timestamp>,
+        settlement_webhook_url -> Nullable<Varchar>,
     }
 }
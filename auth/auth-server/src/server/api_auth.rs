@@ -8,7 +8,7 @@ use tracing::info;
 use uuid::Uuid;
 use warp::filters::path::FullPath;
 
-use crate::{error::AuthServerError, ApiError};
+use crate::{error::AuthServerError, models::ApiKey, ApiError};
 
 use super::{helpers::aes_decrypt, Server};
 
@@ -26,14 +26,14 @@ impl Server {
 
     /// Authorize a request
     ///
-    /// Returns the description for the API key, i.e. a human readable name for
-    /// the entity that is making the request
+    /// Returns the id of the API key along with its description, i.e. a human
+    /// readable name for the entity that is making the request
     pub(crate) async fn authorize_request(
         &self,
         path: &str,
         headers: &HeaderMap,
         body: &[u8],
-    ) -> Result<String, ApiError> {
+    ) -> Result<(Uuid, String), ApiError> {
         // Check API auth
         let api_key = headers
             .get(RENEGADE_API_KEY_HEADER)
@@ -43,7 +43,7 @@ impl Server {
 
         let key_description = self.check_api_key_auth(api_key, path, headers, body).await?;
         info!("Authorized request for entity: {key_description}");
-        Ok(key_description)
+        Ok((api_key, key_description))
     }
 
     /// Check that a request is authorized with a given API key and an HMAC of
@@ -58,18 +58,15 @@ impl Server {
         headers: &HeaderMap,
         body: &[u8],
     ) -> Result<String, AuthServerError> {
-        let (api_secret, description) = self.get_api_secret(api_key).await?;
-        let key = HmacKey::from_base64_string(&api_secret).map_err(AuthServerError::serde)?;
-
-        validate_expiring_auth(path, headers, body, &key).map_err(AuthServerError::unauthorized)?;
-        Ok(description)
+        let (api_secret, entry) = self.get_api_secret(api_key).await?;
+        validate_api_key_auth(&api_secret, &entry, path, headers, body)?;
+        Ok(entry.description)
     }
 
     /// Get the API secret for a given API key
     ///
-    /// Also returns the description for the API key, i.e. a human readable name
-    /// for the entity that is making the request
-    async fn get_api_secret(&self, api_key: Uuid) -> Result<(String, String), AuthServerError> {
+    /// Also returns the entry for the API key
+    async fn get_api_secret(&self, api_key: Uuid) -> Result<(String, ApiKey), AuthServerError> {
         // Fetch the API key entry then decrypt the API secret
         let entry = self.get_api_key_entry(api_key).await?;
         let decrypted = aes_decrypt(&entry.encrypted_key, &self.encryption_key)?;
@@ -77,6 +74,54 @@ impl Server {
             return Err(AuthServerError::ApiKeyInactive);
         }
 
-        Ok((decrypted, entry.description))
+        Ok((decrypted, entry))
+    }
+}
+
+/// Validate a request's HMAC using the given API secret, then check that the
+/// API key has not expired
+///
+/// The signature is checked first so that a key's expiry is only revealed to
+/// holders of its secret
+fn validate_api_key_auth(
+    api_secret: &str,
+    entry: &ApiKey,
+    path: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), AuthServerError> {
+    let key = HmacKey::from_base64_string(api_secret).map_err(AuthServerError::serde)?;
+    validate_expiring_auth(path, headers, body, &key).map_err(AuthServerError::unauthorized)?;
+
+    if entry.is_expired() {
+        return Err(AuthServerError::ApiKeyExpired);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    /// Tests that an expired key with an invalid signature is rejected with the
+    /// generic auth error rather than revealing the expiry
+    #[test]
+    fn test_expired_key_bad_signature() {
+        let api_secret = HmacKey::random().to_base64_string();
+        let entry = ApiKey {
+            id: Uuid::new_v4(),
+            encrypted_key: String::new(),
+            description: "test".to_string(),
+            created_at: SystemTime::now(),
+            is_active: true,
+            expires_at: Some(SystemTime::UNIX_EPOCH),
+            settlement_webhook_url: None,
+        };
+
+        let res = validate_api_key_auth(&api_secret, &entry, "/v0/test", &HeaderMap::new(), &[]);
+        assert!(matches!(res, Err(AuthServerError::Unauthorized(_))));
     }
 }
@@ -3,19 +3,22 @@
 //! At a high level the server must first authenticate the request, then forward
 //! it to the relayer with admin authentication
 
+use auth_server_api::RENEGADE_BUNDLE_ID_HEADER;
 use bytes::Bytes;
-use http::Method;
+use http::{HeaderValue, Method, Response, StatusCode};
 use tracing::{info, instrument, warn};
+use uuid::Uuid;
 use warp::{reject::Rejection, reply::Reply};
 
 use renegade_api::http::external_match::{
     AssembleExternalMatchRequest, ExternalMatchRequest, ExternalMatchResponse, ExternalOrder,
-    ExternalQuoteResponse,
+    ExternalQuoteRequest, ExternalQuoteResponse,
 };
 use renegade_circuit_types::fixed_point::FixedPoint;
 use renegade_common::types::{token::Token, TimestampedPrice};
+use renegade_util::get_current_time_millis;
 
-use super::Server;
+use super::{helpers::is_base_token_allowed, usage_tracker::UsageKind, Server};
 use crate::error::AuthServerError;
 use crate::telemetry::{
     helpers::{
@@ -26,6 +29,7 @@ use crate::telemetry::{
         KEY_DESCRIPTION_METRIC_TAG, REQUEST_ID_METRIC_TAG,
     },
 };
+use crate::ApiError;
 
 /// Handle a proxied request
 impl Server {
@@ -38,7 +42,9 @@ impl Server {
         body: Bytes,
     ) -> Result<impl Reply, Rejection> {
         // Authorize the request
-        let key_desc = self.authorize_request(path.as_str(), &headers, &body).await?;
+        let (key_id, key_desc) = self.authorize_request(path.as_str(), &headers, &body).await?;
+        self.validate_quote_body(&body)?;
+        self.usage_tracker.record(key_id, UsageKind::Quote).await;
 
         // Send the request to the relayer
         let resp =
@@ -64,18 +70,28 @@ impl Server {
         body: Bytes,
     ) -> Result<impl Reply, Rejection> {
         // Authorize the request
-        let key_desc = self.authorize_request(path.as_str(), &headers, &body).await?;
+        let (key_id, key_desc) = self.authorize_request(path.as_str(), &headers, &body).await?;
+        self.validate_assembly_body(&body)?;
         self.check_rate_limit(key_desc.clone()).await?;
+        self.usage_tracker.record(key_id, UsageKind::Assemble).await;
 
         // Send the request to the relayer
-        let resp =
+        let mut resp =
             self.send_admin_request(Method::POST, path.as_str(), headers, body.clone()).await?;
+        let bundle_id = Uuid::new_v4();
+        attach_bundle_id(&mut resp, bundle_id)?;
 
         let resp_clone = resp.body().to_vec();
         let server_clone = self.clone();
         tokio::spawn(async move {
             if let Err(e) = server_clone
-                .handle_quote_assembly_bundle_response(key_desc, &body, &resp_clone)
+                .handle_quote_assembly_bundle_response(
+                    key_id,
+                    bundle_id,
+                    key_desc,
+                    &body,
+                    &resp_clone,
+                )
                 .await
             {
                 warn!("Error handling bundle: {e}");
@@ -94,19 +110,30 @@ impl Server {
         body: Bytes,
     ) -> Result<impl Reply, Rejection> {
         // Authorize the request
-        let key_description = self.authorize_request(path.as_str(), &headers, &body).await?;
+        let (key_id, key_description) =
+            self.authorize_request(path.as_str(), &headers, &body).await?;
+        self.validate_external_match_body(&body)?;
         self.check_rate_limit(key_description.clone()).await?;
+        self.usage_tracker.record(key_id, UsageKind::Assemble).await;
 
         // Send the request to the relayer
-        let resp =
+        let mut resp =
             self.send_admin_request(Method::POST, path.as_str(), headers, body.clone()).await?;
+        let bundle_id = Uuid::new_v4();
+        attach_bundle_id(&mut resp, bundle_id)?;
 
         // Watch the bundle for settlement
         let resp_clone = resp.body().to_vec();
         let server_clone = self.clone();
         tokio::spawn(async move {
             if let Err(e) = server_clone
-                .handle_direct_match_bundle_response(key_description, &body, &resp_clone)
+                .handle_direct_match_bundle_response(
+                    key_id,
+                    bundle_id,
+                    key_description,
+                    &body,
+                    &resp_clone,
+                )
                 .await
             {
                 warn!("Error handling bundle: {e}");
@@ -116,11 +143,75 @@ impl Server {
         Ok(resp)
     }
 
+    // --- Validation --- //
+
+    /// Validate the body of an external quote request
+    fn validate_quote_body(&self, body: &[u8]) -> Result<(), ApiError> {
+        if self.base_token_allowlist.is_empty() {
+            return Ok(());
+        }
+
+        let req: ExternalQuoteRequest =
+            serde_json::from_slice(body).map_err(ApiError::bad_request)?;
+        self.validate_request_body(&req.external_order)
+    }
+
+    /// Validate the body of an external match request
+    fn validate_external_match_body(&self, body: &[u8]) -> Result<(), ApiError> {
+        if self.base_token_allowlist.is_empty() {
+            return Ok(());
+        }
+
+        let req: ExternalMatchRequest =
+            serde_json::from_slice(body).map_err(ApiError::bad_request)?;
+        self.validate_request_body(&req.external_order)
+    }
+
+    /// Validate the body of a quote assembly request
+    fn validate_assembly_body(&self, body: &[u8]) -> Result<(), ApiError> {
+        let req: AssembleExternalMatchRequest =
+            serde_json::from_slice(body).map_err(ApiError::bad_request)?;
+        self.validate_quote_expiry(req.signed_quote.quote.timestamp)?;
+
+        if self.base_token_allowlist.is_empty() {
+            return Ok(());
+        }
+
+        self.validate_request_body(&req.signed_quote.quote.order)
+    }
+
+    /// Validate that a quote with the given timestamp has not expired
+    ///
+    /// Expired quotes are rejected here so that they do not consume a relayer
+    /// round-trip or a rate limit token
+    fn validate_quote_expiry(&self, quote_timestamp_ms: u64) -> Result<(), ApiError> {
+        let age_ms = get_current_time_millis().saturating_sub(quote_timestamp_ms);
+        if age_ms > self.max_quote_age_ms {
+            return Err(ApiError::bad_request("quote expired"));
+        }
+
+        Ok(())
+    }
+
+    /// Validate the order of an external match request before it is forwarded
+    /// to the relayer
+    fn validate_request_body(&self, order: &ExternalOrder) -> Result<(), ApiError> {
+        let base = Token::from_addr_biguint(&order.base_mint);
+        if !is_base_token_allowed(&self.base_token_allowlist, &base) {
+            let msg = format!("Base token {} is not allowed", base.get_addr());
+            return Err(ApiError::bad_request(msg));
+        }
+
+        Ok(())
+    }
+
     // --- Bundle Tracking --- //
 
     /// Handle a bundle response from a quote assembly request
     async fn handle_quote_assembly_bundle_response(
         &self,
+        key_id: Uuid,
+        bundle_id: Uuid,
         key: String,
         req: &[u8],
         resp: &[u8],
@@ -128,12 +219,14 @@ impl Server {
         let req: AssembleExternalMatchRequest =
             serde_json::from_slice(req).map_err(AuthServerError::serde)?;
         let order = req.signed_quote.quote.order;
-        self.handle_bundle_response(key, order, resp).await
+        self.handle_bundle_response(key_id, bundle_id, key, order, resp).await
     }
 
     /// Handle a bundle response from a direct match request
     async fn handle_direct_match_bundle_response(
         &self,
+        key_id: Uuid,
+        bundle_id: Uuid,
         key: String,
         req: &[u8],
         resp: &[u8],
@@ -141,7 +234,7 @@ impl Server {
         let req: ExternalMatchRequest =
             serde_json::from_slice(req).map_err(AuthServerError::serde)?;
         let order = req.external_order;
-        self.handle_bundle_response(key, order, resp).await
+        self.handle_bundle_response(key_id, bundle_id, key, order, resp).await
     }
 
     /// Record and watch a bundle that was forwarded to the client
@@ -149,6 +242,8 @@ impl Server {
     /// This method will await settlement and update metrics, rate limits, etc
     async fn handle_bundle_response(
         &self,
+        key_id: Uuid,
+        bundle_id: Uuid,
         key: String,
         order: ExternalOrder,
         resp: &[u8],
@@ -158,13 +253,23 @@ impl Server {
             serde_json::from_slice(resp).map_err(AuthServerError::serde)?;
 
         // If the bundle settles, increase the API user's a rate limit token balance
-        let did_settle = await_settlement(&match_resp.match_bundle, &self.arbitrum_client).await?;
+        let settlement_tx =
+            await_settlement(&match_resp.match_bundle, &self.arbitrum_client).await?;
+        let did_settle = settlement_tx.is_some();
         if did_settle {
             self.add_rate_limit_token(key.clone()).await;
+            self.usage_tracker.record(key_id, UsageKind::Settlement).await;
+        }
+
+        // Notify the API user of the settlement outcome
+        if let Err(e) =
+            self.maybe_send_settlement_webhook(key_id, bundle_id, &match_resp, settlement_tx).await
+        {
+            warn!("Error sending settlement webhook: {e}");
         }
 
         // Log the bundle and record metrics
-        self.log_bundle(resp)?;
+        self.log_bundle(bundle_id, resp)?;
         record_external_match_metrics(&order, match_resp, key, did_settle).await
     }
 
@@ -188,7 +293,7 @@ impl Server {
     }
 
     /// Log the bundle parameters
-    fn log_bundle(&self, bundle_bytes: &[u8]) -> Result<(), AuthServerError> {
+    fn log_bundle(&self, bundle_id: Uuid, bundle_bytes: &[u8]) -> Result<(), AuthServerError> {
         let resp = serde_json::from_slice::<ExternalMatchResponse>(bundle_bytes)
             .map_err(AuthServerError::serde)?;
 
@@ -197,7 +302,7 @@ impl Server {
         let recv = resp.match_bundle.receive;
         let send = resp.match_bundle.send;
         info!(
-            "Sending bundle(is_buy: {}, recv: {} ({}), send: {} ({})) to client",
+            "Sending bundle {bundle_id} (is_buy: {}, recv: {} ({}), send: {} ({})) to client",
             is_buy, recv.amount, recv.mint, send.amount, send.mint
         );
 
@@ -244,3 +349,17 @@ impl Server {
         Ok(())
     }
 }
+
+/// Attach the bundle id to a match response so that clients may correlate
+/// the bundle with its settlement
+///
+/// Responses that do not carry a bundle (e.g. no match found) are left as-is
+fn attach_bundle_id(resp: &mut Response<Bytes>, bundle_id: Uuid) -> Result<(), ApiError> {
+    if resp.status() != StatusCode::OK {
+        return Ok(());
+    }
+
+    let value = HeaderValue::from_str(&bundle_id.to_string()).map_err(ApiError::internal)?;
+    resp.headers_mut().insert(RENEGADE_BUNDLE_ID_HEADER, value);
+    Ok(())
+}
@@ -1,9 +1,14 @@
 //! Handles key management requests
 
 use crate::models::NewApiKey;
-use auth_server_api::CreateApiKeyRequest;
+use auth_server_api::{
+    CreateApiKeyRequest, KeyUsageResponse, ListApiKeysResponse, SetSettlementWebhookRequest,
+};
 use bytes::Bytes;
 use http::HeaderMap;
+use reqwest::Url;
+use serde::Deserialize;
+use std::time::Duration;
 use uuid::Uuid;
 use warp::{filters::path::FullPath, reject::Rejection, reply::Reply};
 
@@ -11,10 +16,37 @@ use crate::ApiError;
 
 use super::{
     helpers::{aes_encrypt, empty_json_reply},
+    usage_tracker::USAGE_RETENTION,
     Server,
 };
 
+/// The default window over which key usage is reported, in minutes
+const DEFAULT_USAGE_WINDOW_MINUTES: u64 = 60;
+
+/// The query parameters of a key usage request
+#[derive(Debug, Deserialize)]
+pub struct KeyUsageQuery {
+    /// The window over which to count usage, in minutes
+    pub window_minutes: Option<u64>,
+}
+
 impl Server {
+    /// List the metadata of all API keys
+    pub async fn list_keys(
+        &self,
+        path: FullPath,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<impl Reply, Rejection> {
+        // Check management auth on the request
+        self.authorize_management_request(&path, &headers, &body)?;
+
+        // Fetch the keys, omitting their secrets
+        let keys = self.get_all_api_keys().await.map_err(ApiError::internal)?;
+        let resp = ListApiKeysResponse { keys: keys.into_iter().map(Into::into).collect() };
+        Ok(warp::reply::json(&resp))
+    }
+
     /// Add a new API key to the database
     pub async fn add_key(
         &self,
@@ -31,7 +63,8 @@ impl Server {
 
         // Add the key to the database
         let encrypted_secret = aes_encrypt(&req.secret, &self.encryption_key)?;
-        let new_key = NewApiKey::new(req.id, encrypted_secret, req.description);
+        let expires_at = req.expires_at.map(Into::into);
+        let new_key = NewApiKey::new(req.id, encrypted_secret, req.description, expires_at);
         self.add_key_query(new_key).await.map_err(ApiError::internal)?;
 
         Ok(empty_json_reply())
@@ -52,4 +85,58 @@ impl Server {
         self.expire_key_query(key_id).await?;
         Ok(empty_json_reply())
     }
+
+    /// Set the settlement webhook URL for an API key
+    pub async fn set_settlement_webhook(
+        &self,
+        key_id: Uuid,
+        path: FullPath,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<impl Reply, Rejection> {
+        // Check management auth on the request
+        self.authorize_management_request(&path, &headers, &body)?;
+
+        // Deserialize and validate the request
+        let req: SetSettlementWebhookRequest =
+            serde_json::from_slice(&body).map_err(ApiError::bad_request)?;
+        if let Some(url) = &req.url {
+            Url::parse(url).map_err(ApiError::bad_request)?;
+        }
+
+        self.set_settlement_webhook_query(key_id, req.url).await.map_err(ApiError::from)?;
+        Ok(empty_json_reply())
+    }
+
+    /// Get the usage statistics of an API key
+    pub async fn get_key_usage(
+        &self,
+        key_id: Uuid,
+        query: KeyUsageQuery,
+        path: FullPath,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<impl Reply, Rejection> {
+        // Check management auth on the request
+        self.authorize_management_request(&path, &headers, &body)?;
+
+        let max_window_minutes = USAGE_RETENTION.as_secs() / 60;
+        let window_minutes = query.window_minutes.unwrap_or(DEFAULT_USAGE_WINDOW_MINUTES);
+        if window_minutes == 0 || window_minutes > max_window_minutes {
+            let msg = format!("window_minutes must be between 1 and {max_window_minutes}");
+            return Err(ApiError::bad_request(msg).into());
+        }
+
+        let window = Duration::from_secs(window_minutes * 60);
+        let usage = self.usage_tracker.usage(key_id, window).await;
+        let resp = KeyUsageResponse {
+            id: key_id,
+            window_minutes,
+            quote_requests: usage.quotes,
+            assemble_requests: usage.assembles,
+            successful_settlements: usage.settlements,
+        };
+
+        Ok(warp::reply::json(&resp))
+    }
 }
@@ -6,7 +6,9 @@ use aes_gcm::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use rand::thread_rng;
+use renegade_common::types::token::Token;
 use serde_json::json;
+use std::collections::HashSet;
 use warp::reply::Reply;
 
 use crate::error::AuthServerError;
@@ -19,6 +21,32 @@ pub fn empty_json_reply() -> impl Reply {
     warp::reply::json(&json!({}))
 }
 
+/// Parse a base token allowlist from the given entries, normalizing each
+/// ticker or address to lowercase and skipping empty entries
+pub fn parse_base_token_allowlist(entries: &[String]) -> HashSet<String> {
+    entries.iter().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect()
+}
+
+/// Check whether a base token is permitted by the given allowlist
+///
+/// The allowlist holds lowercase tickers or addresses, an empty allowlist
+/// permits all tokens
+pub fn is_base_token_allowed(allowlist: &HashSet<String>, base: &Token) -> bool {
+    allowlist_contains(allowlist, &base.get_addr(), base.get_ticker().as_deref())
+}
+
+/// Check whether a token with the given address and ticker is permitted by
+/// the given allowlist, ignoring case
+fn allowlist_contains(allowlist: &HashSet<String>, addr: &str, ticker: Option<&str>) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+
+    let addr = addr.to_lowercase();
+    let ticker = ticker.map(|t| t.to_lowercase());
+    allowlist.contains(&addr) || ticker.is_some_and(|t| allowlist.contains(&t))
+}
+
 /// AES encrypt a value
 ///
 /// Returns a base64 encoded string of the format [nonce, ciphertext]
@@ -56,6 +84,63 @@ mod tests {
 
     use super::*;
 
+    /// A base token address used in the allowlist tests
+    const BASE_ADDR: &str = "0x82af49447d8a07e3bd95bd0d56f35241523fbab1";
+    /// A second base token address, absent from the allowlist
+    const OTHER_BASE_ADDR: &str = "0x2f2a2543b76a4166549f7aab2e75bef0aefc5b0f";
+
+    /// Tests that an allowlisted base token is permitted
+    #[test]
+    fn test_base_token_allowed() {
+        let allowlist = HashSet::from([BASE_ADDR.to_string()]);
+        let base = Token::from_addr(BASE_ADDR);
+        assert!(is_base_token_allowed(&allowlist, &base));
+    }
+
+    /// Tests that a base token missing from the allowlist is rejected
+    #[test]
+    fn test_base_token_disallowed() {
+        let allowlist = HashSet::from([BASE_ADDR.to_string()]);
+        let base = Token::from_addr(OTHER_BASE_ADDR);
+        assert!(!is_base_token_allowed(&allowlist, &base));
+    }
+
+    /// Tests that an empty allowlist permits all base tokens
+    #[test]
+    fn test_base_token_allowlist_unset() {
+        let allowlist = HashSet::new();
+        let base = Token::from_addr(OTHER_BASE_ADDR);
+        assert!(is_base_token_allowed(&allowlist, &base));
+    }
+
+    /// Tests that a token is permitted by a ticker entry in the allowlist
+    #[test]
+    fn test_base_token_allowed_by_ticker() {
+        let allowlist = parse_base_token_allowlist(&["WETH".to_string()]);
+        assert!(allowlist_contains(&allowlist, OTHER_BASE_ADDR, Some("WETH")));
+        assert!(!allowlist_contains(&allowlist, OTHER_BASE_ADDR, Some("WBTC")));
+        assert!(!allowlist_contains(&allowlist, OTHER_BASE_ADDR, None));
+    }
+
+    /// Tests that allowlist matching ignores case on both the entries and the
+    /// token
+    #[test]
+    fn test_base_token_allowlist_case_insensitive() {
+        let checksummed = "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1";
+        let allowlist = parse_base_token_allowlist(&[checksummed.to_string(), "weth".to_string()]);
+        assert!(allowlist_contains(&allowlist, BASE_ADDR, None));
+        assert!(allowlist_contains(&allowlist, &BASE_ADDR.to_uppercase(), None));
+        assert!(allowlist_contains(&allowlist, OTHER_BASE_ADDR, Some("WETH")));
+    }
+
+    /// Tests that allowlist entries are trimmed and empty entries dropped
+    #[test]
+    fn test_parse_base_token_allowlist() {
+        let entries = vec![" WETH ".to_string(), String::new(), BASE_ADDR.to_string()];
+        let allowlist = parse_base_token_allowlist(&entries);
+        assert_eq!(allowlist, HashSet::from(["weth".to_string(), BASE_ADDR.to_string()]));
+    }
+
     /// Tests AES encryption and decryption
     #[test]
     fn test_aes_encrypt_decrypt() {
@@ -7,8 +7,13 @@ mod handle_key_management;
 mod helpers;
 mod queries;
 mod rate_limiter;
+mod settlement_webhook;
+mod usage_tracker;
 
-use crate::{error::AuthServerError, models::ApiKey, ApiError, Cli};
+use crate::{
+    error::AuthServerError, models::ApiKey, telemetry::helpers::record_relayer_request_latency,
+    ApiError, Cli,
+};
 use base64::{engine::general_purpose, Engine};
 use bb8::{Pool, PooledConnection};
 use bytes::Bytes;
@@ -18,6 +23,7 @@ use diesel_async::{
     pooled_connection::{AsyncDieselConnectionManager, ManagerConfig},
     AsyncPgConnection,
 };
+use helpers::parse_base_token_allowlist;
 use http::{HeaderMap, Method, Response};
 use native_tls::TlsConnector;
 use postgres_native_tls::MakeTlsConnector;
@@ -26,11 +32,18 @@ use renegade_api::auth::add_expiring_auth_to_headers;
 use renegade_arbitrum_client::client::ArbitrumClient;
 use renegade_common::types::wallet::keychain::HmacKey;
 use reqwest::Client;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::RwLock;
 use tracing::error;
+use usage_tracker::KeyUsageTracker;
 use uuid::Uuid;
 
+pub use handle_key_management::KeyUsageQuery;
+
 /// The duration for which the admin authentication is valid
 const ADMIN_AUTH_DURATION_MS: u64 = 5_000; // 5 seconds
 
@@ -62,6 +75,16 @@ pub struct Server {
     pub arbitrum_client: ArbitrumClient,
     /// The rate limiter
     pub rate_limiter: BundleRateLimiter,
+    /// The set of base tokens allowed in external matches, normalized to
+    /// lowercase
+    ///
+    /// An empty set allows all base tokens
+    pub base_token_allowlist: Arc<HashSet<String>>,
+    /// The per-key usage tracker
+    pub usage_tracker: KeyUsageTracker,
+    /// The maximum age of a signed quote submitted for assembly, in
+    /// milliseconds
+    pub max_quote_age_ms: u64,
 }
 
 impl Server {
@@ -81,6 +104,13 @@ impl Server {
             HmacKey::from_base64_string(&args.relayer_admin_key).map_err(AuthServerError::setup)?;
 
         let rate_limiter = BundleRateLimiter::new(args.bundle_rate_limit);
+        let base_token_allowlist = parse_base_token_allowlist(&args.base_token_allowlist);
+
+        // Bound the time spent waiting on the relayer
+        let client = Client::builder()
+            .timeout(Duration::from_millis(args.relayer_timeout_ms))
+            .build()
+            .map_err(AuthServerError::setup)?;
 
         Ok(Self {
             db_pool: Arc::new(db_pool),
@@ -89,9 +119,12 @@ impl Server {
             management_key,
             encryption_key,
             api_key_cache: Arc::new(RwLock::new(UnboundCache::new())),
-            client: Client::new(),
+            client,
             arbitrum_client,
             rate_limiter,
+            base_token_allowlist: Arc::new(base_token_allowlist),
+            usage_tracker: KeyUsageTracker::new(),
+            max_quote_age_ms: args.max_quote_age_ms,
         })
     }
 
@@ -114,11 +147,29 @@ impl Server {
         // Forward the request to the relayer
         let url = format!("{}{}", self.relayer_url, path);
         let req = self.client.request(method, &url).headers(headers).body(body);
+
+        let start = Instant::now();
+        let res = Self::read_relayer_response(req).await;
+        let timed_out = matches!(res, Err(ApiError::GatewayTimeout));
+        record_relayer_request_latency(path, start.elapsed(), timed_out);
+
+        res
+    }
+
+    /// Send a request to the relayer and read its response
+    async fn read_relayer_response(
+        req: reqwest::RequestBuilder,
+    ) -> Result<Response<Bytes>, ApiError> {
         match req.send().await {
             Ok(resp) => {
                 let status = resp.status();
                 let headers = resp.headers().clone();
                 let body = resp.bytes().await.map_err(|e| {
+                    if e.is_timeout() {
+                        error!("Timed out reading relayer response body: {e}");
+                        return ApiError::GatewayTimeout;
+                    }
+
                     ApiError::internal(format!("Failed to read response body: {e}"))
                 })?;
 
@@ -128,6 +179,10 @@ impl Server {
 
                 Ok(response)
             },
+            Err(e) if e.is_timeout() => {
+                error!("Timed out proxying request: {}", e);
+                Err(ApiError::GatewayTimeout)
+            },
             Err(e) => {
                 error!("Error proxying request: {}", e);
                 Err(ApiError::internal(e))
@@ -177,6 +232,14 @@ impl Server {
         cache.cache_set(api_key.id, api_key);
     }
 
+    /// Set the settlement webhook URL of a cached API key
+    pub async fn set_cached_settlement_webhook(&self, id: Uuid, url: Option<String>) {
+        let mut cache = self.api_key_cache.write().await;
+        if let Some(key) = cache.cache_get_mut(&id) {
+            key.settlement_webhook_url = url;
+        }
+    }
+
     /// Mark a cached API key as expired
     pub async fn mark_cached_key_expired(&self, id: Uuid) {
         let mut cache = self.api_key_cache.write().await;
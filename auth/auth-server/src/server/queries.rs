@@ -46,6 +46,16 @@ impl Server {
         Ok(key)
     }
 
+    /// Get all API key entries, ordered by creation time
+    pub async fn get_all_api_keys(&self) -> Result<Vec<ApiKey>, AuthServerError> {
+        let mut conn = self.get_db_conn().await?;
+        api_keys::table
+            .order(api_keys::created_at.asc())
+            .load::<ApiKey>(&mut conn)
+            .await
+            .map_err(AuthServerError::db)
+    }
+
     // --- Setters --- //
 
     /// Add a new API key to the database
@@ -64,6 +74,30 @@ impl Server {
         Ok(())
     }
 
+    /// Set the settlement webhook URL for an API key
+    pub async fn set_settlement_webhook_query(
+        &self,
+        key_id: Uuid,
+        url: Option<String>,
+    ) -> Result<(), AuthServerError> {
+        // Update the database
+        let mut conn = self.get_db_conn().await?;
+        let rows = diesel::update(api_keys::table.filter(api_keys::id.eq(key_id)))
+            .set(api_keys::settlement_webhook_url.eq(url.clone()))
+            .execute(&mut conn)
+            .await
+            .map_err(AuthServerError::db)?;
+        drop(conn); // Drop the connection to release the mutable borrow on `self`
+
+        if rows == 0 {
+            return Err(AuthServerError::bad_request(format!("API key not found: {key_id}")));
+        }
+
+        // Update the cached key
+        self.set_cached_settlement_webhook(key_id, url).await;
+        Ok(())
+    }
+
     /// Expire an existing API key
     pub async fn expire_key_query(&self, key_id: Uuid) -> Result<(), AuthServerError> {
         // Update the database
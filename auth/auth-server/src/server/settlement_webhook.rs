@@ -0,0 +1,87 @@
+//! Delivers settlement webhooks for bundles forwarded to API keys
+//!
+//! Delivery is best-effort: webhooks are sent from a detached task and retried
+//! a bounded number of times, so a slow or failing webhook never blocks
+//! settlement tracking
+
+use std::time::Duration;
+
+use auth_server_api::SettlementWebhookPayload;
+use ethers::types::TxHash;
+use renegade_api::http::external_match::ExternalMatchResponse;
+use reqwest::Client;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::error::AuthServerError;
+
+use super::Server;
+
+/// The number of attempts made to deliver a settlement webhook
+const WEBHOOK_MAX_ATTEMPTS: usize = 3;
+/// The delay between settlement webhook delivery attempts
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The timeout applied to each settlement webhook delivery attempt
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Server {
+    /// Send a settlement webhook for the given bundle if the API key has a
+    /// webhook configured
+    pub(crate) async fn maybe_send_settlement_webhook(
+        &self,
+        key_id: Uuid,
+        bundle_id: Uuid,
+        match_resp: &ExternalMatchResponse,
+        settlement_tx: Option<TxHash>,
+    ) -> Result<(), AuthServerError> {
+        let key = self.get_api_key_entry(key_id).await?;
+        let url = match key.settlement_webhook_url {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+
+        let match_result = &match_resp.match_bundle.match_result;
+        let payload = SettlementWebhookPayload {
+            bundle_id,
+            key_description: key.description,
+            settled: settlement_tx.is_some(),
+            tx_hash: settlement_tx.map(|tx| format!("{tx:#x}")),
+            base_mint: match_result.base_mint.clone(),
+            quote_mint: match_result.quote_mint.clone(),
+            base_amount: match_result.base_amount,
+            quote_amount: match_result.quote_amount,
+        };
+
+        let client = self.client.clone();
+        tokio::spawn(deliver_webhook(client, url, payload));
+        Ok(())
+    }
+}
+
+/// Deliver a settlement webhook, retrying on failure
+async fn deliver_webhook(client: Client, url: String, payload: SettlementWebhookPayload) {
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        let res = client
+            .post(&url)
+            .json(&payload)
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+
+        match res {
+            Ok(_) => {
+                info!("Delivered settlement webhook for bundle {}", payload.bundle_id);
+                return;
+            },
+            Err(e) => warn!(
+                "Webhook attempt {attempt}/{WEBHOOK_MAX_ATTEMPTS} for bundle {} failed: {e}",
+                payload.bundle_id
+            ),
+        }
+
+        if attempt < WEBHOOK_MAX_ATTEMPTS {
+            tokio::time::sleep(WEBHOOK_RETRY_DELAY).await;
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Tracks per-key usage of the proxied endpoints
+//!
+//! Usage is held in memory and retained for a fixed window, so statistics are
+//! reset when the server restarts
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// The duration for which usage events are retained
+pub const USAGE_RETENTION: Duration = Duration::from_secs(24 * 60 * 60); // 1 day
+
+/// The kind of usage event recorded for a key
+#[derive(Clone, Copy, Debug)]
+pub enum UsageKind {
+    /// An external quote request
+    Quote,
+    /// A quote assembly or direct match request
+    Assemble,
+    /// A bundle that settled on-chain
+    Settlement,
+}
+
+/// The number of usage events of each kind within a window
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UsageCounts {
+    /// The number of quote requests
+    pub quotes: u64,
+    /// The number of quote assembly and direct match requests
+    pub assembles: u64,
+    /// The number of bundles that settled
+    pub settlements: u64,
+}
+
+/// The timestamps of usage events for a single key
+#[derive(Default)]
+struct KeyUsageEvents {
+    /// The timestamps of quote requests
+    quotes: VecDeque<Instant>,
+    /// The timestamps of quote assembly and direct match requests
+    assembles: VecDeque<Instant>,
+    /// The timestamps of settled bundles
+    settlements: VecDeque<Instant>,
+}
+
+impl KeyUsageEvents {
+    /// Get the event queue for a given kind
+    fn events_mut(&mut self, kind: UsageKind) -> &mut VecDeque<Instant> {
+        match kind {
+            UsageKind::Quote => &mut self.quotes,
+            UsageKind::Assemble => &mut self.assembles,
+            UsageKind::Settlement => &mut self.settlements,
+        }
+    }
+}
+
+/// The per-key usage tracker
+#[derive(Clone, Default)]
+pub struct KeyUsageTracker {
+    /// The usage events, indexed by API key id
+    usage: Arc<Mutex<HashMap<Uuid, KeyUsageEvents>>>,
+}
+
+impl KeyUsageTracker {
+    /// Create a new usage tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a usage event for the given key
+    pub async fn record(&self, key_id: Uuid, kind: UsageKind) {
+        self.record_at(key_id, kind, Instant::now()).await
+    }
+
+    /// Record a usage event for the given key at the given time
+    async fn record_at(&self, key_id: Uuid, kind: UsageKind, now: Instant) {
+        let mut usage = self.usage.lock().await;
+        let events = usage.entry(key_id).or_default().events_mut(kind);
+
+        // Prune events that have fallen out of the retention window
+        while events.front().is_some_and(|ts| now.duration_since(*ts) > USAGE_RETENTION) {
+            events.pop_front();
+        }
+        events.push_back(now);
+    }
+
+    /// Count the usage events for the given key within the window
+    ///
+    /// The window is capped at the retention duration
+    pub async fn usage(&self, key_id: Uuid, window: Duration) -> UsageCounts {
+        self.usage_at(key_id, window, Instant::now()).await
+    }
+
+    /// Count the usage events for the given key within the window ending at
+    /// the given time
+    async fn usage_at(&self, key_id: Uuid, window: Duration, now: Instant) -> UsageCounts {
+        let window = window.min(USAGE_RETENTION);
+        let count = |events: &VecDeque<Instant>| {
+            events.iter().filter(|ts| now.duration_since(**ts) <= window).count() as u64
+        };
+
+        let usage = self.usage.lock().await;
+        usage
+            .get(&key_id)
+            .map(|events| UsageCounts {
+                quotes: count(&events.quotes),
+                assembles: count(&events.assembles),
+                settlements: count(&events.settlements),
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that usage is counted separately for each kind and key
+    #[tokio::test]
+    async fn test_usage_counts_per_kind() {
+        let tracker = KeyUsageTracker::new();
+        let key_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        tracker.record_at(key_id, UsageKind::Quote, now).await;
+        tracker.record_at(key_id, UsageKind::Quote, now).await;
+        tracker.record_at(key_id, UsageKind::Assemble, now).await;
+        tracker.record_at(Uuid::new_v4(), UsageKind::Settlement, now).await;
+
+        let counts = tracker.usage_at(key_id, USAGE_RETENTION, now).await;
+        assert_eq!(counts.quotes, 2);
+        assert_eq!(counts.assembles, 1);
+        assert_eq!(counts.settlements, 0);
+    }
+
+    /// Tests that only events within the requested window are counted
+    #[tokio::test]
+    async fn test_usage_window() {
+        let tracker = KeyUsageTracker::new();
+        let key_id = Uuid::new_v4();
+        let start = Instant::now();
+        let hour = Duration::from_secs(60 * 60);
+
+        tracker.record_at(key_id, UsageKind::Quote, start).await;
+        tracker.record_at(key_id, UsageKind::Quote, start + 2 * hour).await;
+
+        let now = start + 2 * hour;
+        assert_eq!(tracker.usage_at(key_id, hour, now).await.quotes, 1);
+        assert_eq!(tracker.usage_at(key_id, 3 * hour, now).await.quotes, 2);
+    }
+
+    /// Tests that events older than the retention window are pruned on record
+    #[tokio::test]
+    async fn test_usage_retention_pruning() {
+        let tracker = KeyUsageTracker::new();
+        let key_id = Uuid::new_v4();
+        let start = Instant::now();
+        let later = start + USAGE_RETENTION + Duration::from_secs(1);
+
+        tracker.record_at(key_id, UsageKind::Quote, start).await;
+        tracker.record_at(key_id, UsageKind::Assemble, start).await;
+        tracker.record_at(key_id, UsageKind::Quote, later).await;
+
+        // The stale quote is pruned, while the assemble queue is untouched until
+        // its next record
+        let usage = tracker.usage.lock().await;
+        let events = usage.get(&key_id).unwrap();
+        assert_eq!(events.quotes.len(), 1);
+        assert_eq!(events.assembles.len(), 1);
+        drop(usage);
+
+        // Windows beyond the retention duration are capped
+        let counts = tracker.usage_at(key_id, 2 * USAGE_RETENTION, later).await;
+        assert_eq!(counts.quotes, 1);
+        assert_eq!(counts.assembles, 0);
+    }
+}
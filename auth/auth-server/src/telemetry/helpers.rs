@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use alloy_sol_types::SolCall;
 use contracts_common::types::MatchPayload;
+use ethers::types::TxHash;
 use renegade_api::http::external_match::{
     AtomicMatchApiBundle, ExternalMatchResponse, ExternalOrder,
 };
@@ -23,10 +24,12 @@ use crate::{
     error::AuthServerError,
     telemetry::labels::{
         ASSET_METRIC_TAG, BASE_ASSET_METRIC_TAG, DECIMAL_CORRECTION_FIXED_METRIC_TAG,
-        EXTERNAL_MATCH_BASE_VOLUME, EXTERNAL_MATCH_FILL_RATIO, EXTERNAL_MATCH_QUOTE_VOLUME,
-        EXTERNAL_MATCH_SETTLED_BASE_VOLUME, EXTERNAL_MATCH_SETTLED_QUOTE_VOLUME,
-        EXTERNAL_ORDER_BASE_VOLUME, EXTERNAL_ORDER_QUOTE_VOLUME, KEY_DESCRIPTION_METRIC_TAG,
-        NUM_EXTERNAL_MATCH_REQUESTS, REQUEST_ID_METRIC_TAG, SETTLEMENT_STATUS_TAG,
+        ENDPOINT_METRIC_TAG, EXTERNAL_MATCH_BASE_VOLUME, EXTERNAL_MATCH_FILL_RATIO,
+        EXTERNAL_MATCH_QUOTE_VOLUME, EXTERNAL_MATCH_SETTLED_BASE_VOLUME,
+        EXTERNAL_MATCH_SETTLED_QUOTE_VOLUME, EXTERNAL_ORDER_BASE_VOLUME,
+        EXTERNAL_ORDER_QUOTE_VOLUME, KEY_DESCRIPTION_METRIC_TAG, NUM_EXTERNAL_MATCH_REQUESTS,
+        RELAYER_REQUEST_LATENCY_MS, REQUEST_ID_METRIC_TAG, SETTLEMENT_STATUS_TAG,
+        TIMED_OUT_METRIC_TAG,
     },
 };
 
@@ -204,6 +207,15 @@ pub(crate) fn record_endpoint_metrics(
     metrics::counter!(metric_name, &labels).increment(1);
 }
 
+/// Records the round-trip latency of a request proxied to the relayer
+pub(crate) fn record_relayer_request_latency(endpoint: &str, latency: Duration, timed_out: bool) {
+    let labels = vec![
+        (ENDPOINT_METRIC_TAG.to_string(), endpoint.to_string()),
+        (TIMED_OUT_METRIC_TAG.to_string(), timed_out.to_string()),
+    ];
+    metrics::histogram!(RELAYER_REQUEST_LATENCY_MS, &labels).record(latency.as_millis() as f64);
+}
+
 /// Records the fill ratio (matched quote amount / requested quote amount)
 pub(crate) fn record_fill_ratio(
     requested_quote_amount: u128,
@@ -259,19 +271,20 @@ pub(crate) async fn record_external_match_metrics(
 
 /// Await the result of the atomic match settlement to be submitted on-chain
 ///
-/// Returns `true` if the settlement succeeded on-chain, `false` otherwise
+/// Returns the hash of the settlement transaction if the settlement succeeded
+/// on-chain, `None` otherwise
 pub(crate) async fn await_settlement(
     match_bundle: &AtomicMatchApiBundle,
     arbitrum_client: &ArbitrumClient,
-) -> Result<bool, AuthServerError> {
+) -> Result<Option<TxHash>, AuthServerError> {
     let nullifier = extract_nullifier_from_match_bundle(match_bundle)?;
     let res = arbitrum_client.await_nullifier_spent(nullifier, ATOMIC_SETTLEMENT_TIMEOUT).await;
 
-    let did_settle = res.is_ok();
-    if !did_settle {
+    let tx_hash = res.ok();
+    if tx_hash.is_none() {
         info!("atomic match settlement not observed on-chain");
     }
-    Ok(did_settle)
+    Ok(tx_hash)
 }
 
 /// Extracts the nullifier from a match bundle's settlement transaction
@@ -29,6 +29,10 @@ pub const EXTERNAL_MATCH_SETTLED_BASE_VOLUME: &str = "external_match_settled_bas
 /// Metric describing the volume of the quote asset in an external match
 pub const EXTERNAL_MATCH_SETTLED_QUOTE_VOLUME: &str = "external_match_settled_quote_volume";
 
+/// Metric describing the round-trip latency of a request proxied to the
+/// relayer, in milliseconds
+pub const RELAYER_REQUEST_LATENCY_MS: &str = "relayer_request_latency_ms";
+
 // ---------------
 // | METRIC TAGS |
 // ---------------
@@ -46,3 +50,7 @@ pub const REQUEST_ID_METRIC_TAG: &str = "request_id";
 pub const BASE_ASSET_METRIC_TAG: &str = "base_asset";
 /// Metric tag to indicate data was recorded post decimal correction fix
 pub const DECIMAL_CORRECTION_FIXED_METRIC_TAG: &str = "post_decimal_fix";
+/// Metric tag for the relayer endpoint a request was proxied to
+pub const ENDPOINT_METRIC_TAG: &str = "endpoint";
+/// Metric tag for whether a proxied request timed out
+pub const TIMED_OUT_METRIC_TAG: &str = "timed_out";
@@ -6,10 +6,16 @@
 #![deny(clippy::needless_pass_by_value)]
 #![deny(clippy::needless_pass_by_ref_mut)]
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// The API endpoint for screening an address for compliance
 pub const WALLET_SCREEN_PATH: &str = "/v0/check-compliance";
+/// The API endpoint for screening a batch of addresses for compliance
+pub const WALLET_SCREEN_BATCH_PATH: &str = "/v0/check-compliance-batch";
+/// The maximum number of addresses that may be screened in a single batch
+pub const MAX_BATCH_SIZE: usize = 100;
 
 /// The response type for a compliance check
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,12 +24,44 @@ pub struct ComplianceCheckResponse {
     pub compliance_status: ComplianceStatus,
 }
 
+/// The request type for a batch compliance check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchComplianceCheckRequest {
+    /// The addresses to screen
+    pub addresses: Vec<String>,
+}
+
+/// The response type for a batch compliance check
+///
+/// Both maps are keyed by the addresses exactly as given in the request.
+/// Addresses are compared case-insensitively, so differently cased copies of
+/// an address share a status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchComplianceCheckResponse {
+    /// The compliance status of each wallet that was screened, indexed by
+    /// address
+    pub compliance_statuses: HashMap<String, ComplianceStatus>,
+    /// The error encountered for each wallet that could not be screened,
+    /// indexed by address
+    #[serde(default)]
+    pub errors: HashMap<String, String>,
+}
+
 /// The status on compliance for a wallet
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ComplianceStatus {
     /// The wallet is compliant
     Compliant,
     /// The wallet is not compliant
-    #[allow(missing_docs)]
-    NotCompliant { reason: String },
+    NotCompliant {
+        /// A human-readable reason for the classification
+        reason: String,
+        /// The largest exposure percentage across the exposure rules the
+        /// wallet triggered, if the provider reported any
+        #[serde(default)]
+        max_exposure_pct: Option<f64>,
+        /// The risk categories the wallet was flagged under, e.g. `sanctions`
+        #[serde(default)]
+        categories: Vec<String>,
+    },
 }
@@ -0,0 +1 @@
+ALTER TABLE wallet_compliance DROP COLUMN screened_at;
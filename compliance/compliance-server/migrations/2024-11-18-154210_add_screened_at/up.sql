@@ -0,0 +1,5 @@
+-- Track when each wallet was last screened so stale entries can be re-screened
+ALTER TABLE wallet_compliance ADD COLUMN screened_at TIMESTAMP;
+UPDATE wallet_compliance SET screened_at = created_at;
+ALTER TABLE wallet_compliance ALTER COLUMN screened_at SET NOT NULL;
+ALTER TABLE wallet_compliance ALTER COLUMN screened_at SET DEFAULT NOW();
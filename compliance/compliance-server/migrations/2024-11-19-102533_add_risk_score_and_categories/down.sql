@@ -0,0 +1,2 @@
+ALTER TABLE wallet_compliance DROP COLUMN categories;
+ALTER TABLE wallet_compliance DROP COLUMN risk_score;
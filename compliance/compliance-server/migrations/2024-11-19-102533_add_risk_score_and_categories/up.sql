@@ -0,0 +1,3 @@
+-- Store the provider's risk score and flagged categories for each wallet
+ALTER TABLE wallet_compliance ADD COLUMN risk_score DOUBLE PRECISION;
+ALTER TABLE wallet_compliance ADD COLUMN categories TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+-- The original casing of the addresses is not retained, so this migration is
+-- not reverted
+SELECT 1;
@@ -0,0 +1,10 @@
+-- Lowercase cached wallet addresses so that lookups are case-insensitive,
+-- keeping the most recently screened entry when addresses collide
+DELETE FROM wallet_compliance a
+USING wallet_compliance b
+WHERE lower(a.address) = lower(b.address)
+    AND a.address <> b.address
+    AND (a.screened_at < b.screened_at
+        OR (a.screened_at = b.screened_at AND a.address < b.address));
+
+UPDATE wallet_compliance SET address = lower(address) WHERE address <> lower(address);
@@ -0,0 +1 @@
+ALTER TABLE wallet_compliance RENAME COLUMN max_exposure_pct TO risk_score;
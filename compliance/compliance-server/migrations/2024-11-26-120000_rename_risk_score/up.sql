@@ -0,0 +1,3 @@
+-- The stored value is the largest exposure percentage across the triggered
+-- exposure rules, not a provider risk score
+ALTER TABLE wallet_compliance RENAME COLUMN risk_score TO max_exposure_pct;
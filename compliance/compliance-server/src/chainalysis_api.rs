@@ -1,9 +1,14 @@
 //! Helpers for interacting with the chainalysis API
 
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use crate::{db::ComplianceEntry, error::ComplianceServerError};
+use crate::{
+    db::ComplianceEntry,
+    error::ComplianceServerError,
+    provider::{ComplianceProvider, RiskLevel},
+};
 
 // -------------
 // | API Types |
@@ -31,25 +36,121 @@ pub struct RiskAssessmentResponse {
     /// The reason for the risk assessment
     #[serde(rename = "riskReason")]
     pub risk_reason: Option<String>,
+    /// The cluster the address belongs to, if it has been identified
+    #[serde(default)]
+    pub cluster: Option<Cluster>,
+    /// The identifications attached directly to the address
+    #[serde(default, rename = "addressIdentifications")]
+    pub address_identifications: Vec<AddressIdentification>,
+    /// The exposure rules triggered by the address
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+}
+
+/// An entity cluster identified by chainalysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    /// The name of the cluster
+    pub name: Option<String>,
+    /// The category of the cluster
+    pub category: Option<String>,
+}
+
+/// An identification attached directly to an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressIdentification {
+    /// The category of the identification
+    pub category: String,
+}
+
+/// An exposure rule triggered by an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trigger {
+    /// The category of the exposure
+    pub category: String,
+    /// The fraction of the address' exposure attributed to the category
+    pub percentage: Option<f64>,
 }
 
 impl RiskAssessmentResponse {
     /// Get a compliance entry from the risk assessment
-    pub fn as_compliance_entry(self) -> ComplianceEntry {
-        // We allow low and medium risk entries, high and severe are marked
-        // non-compliant
-        let compliant = match self.risk.as_str() {
-            "Low" | "Medium" => true,
-            "High" | "Severe" => false,
-            x => {
+    ///
+    /// Addresses assessed below the risk threshold are marked compliant, those
+    /// at or above it are marked non-compliant
+    pub fn as_compliance_entry(self, risk_threshold: RiskLevel) -> ComplianceEntry {
+        let compliant = match RiskLevel::from_assessment(&self.risk) {
+            Some(level) => level < risk_threshold,
+            None => {
                 // For now we don't block on an unknown assessment, this should be unreachable
-                warn!("Unexpected risk assessment: {x}");
+                warn!("Unexpected risk assessment: {}", self.risk);
                 true
             },
         };
 
+        let max_exposure_pct = self.max_exposure_pct();
+        let categories = self.categories();
         let risk_reason = self.risk_reason.unwrap_or_default();
-        ComplianceEntry::new(self.address, compliant, self.risk, risk_reason)
+        ComplianceEntry::new(
+            self.address,
+            compliant,
+            self.risk,
+            risk_reason,
+            max_exposure_pct,
+            categories,
+        )
+    }
+
+    /// Get the largest exposure percentage across the exposure rules triggered
+    /// by the address
+    ///
+    /// This is taken directly from the triggers in the response, Chainalysis
+    /// does not return a single numeric score
+    fn max_exposure_pct(&self) -> Option<f64> {
+        self.triggers.iter().filter_map(|t| t.percentage).reduce(f64::max)
+    }
+
+    /// Get the deduplicated risk categories the address was flagged under
+    fn categories(&self) -> Vec<String> {
+        let cluster_category = self.cluster.as_ref().and_then(|c| c.category.clone());
+        let identification_categories =
+            self.address_identifications.iter().map(|id| id.category.clone());
+        let trigger_categories = self.triggers.iter().map(|t| t.category.clone());
+
+        let mut categories: Vec<String> = cluster_category
+            .into_iter()
+            .chain(identification_categories)
+            .chain(trigger_categories)
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+}
+
+// -----------------
+// | Provider Impl |
+// -----------------
+
+/// A compliance provider backed by the chainalysis API
+#[derive(Clone)]
+pub struct ChainalysisProvider {
+    /// The chainalysis API key
+    api_key: String,
+    /// The risk level at or above which an address is non-compliant
+    risk_threshold: RiskLevel,
+}
+
+impl ChainalysisProvider {
+    /// Constructor
+    pub fn new(api_key: String, risk_threshold: RiskLevel) -> Self {
+        Self { api_key, risk_threshold }
+    }
+}
+
+#[async_trait]
+impl ComplianceProvider for ChainalysisProvider {
+    async fn screen(&self, address: &str) -> Result<ComplianceEntry, ComplianceServerError> {
+        query_chainalysis(address, &self.api_key, self.risk_threshold).await
     }
 }
 
@@ -61,13 +162,14 @@ impl RiskAssessmentResponse {
 pub async fn query_chainalysis(
     wallet_address: &str,
     chainalysis_api_key: &str,
+    risk_threshold: RiskLevel,
 ) -> Result<ComplianceEntry, ComplianceServerError> {
     // 1. Register the wallet
     register_addr(wallet_address, chainalysis_api_key).await?;
 
     // 2. Query the risk assessment
     let risk_assessment = query_risk_assessment(wallet_address, chainalysis_api_key).await?;
-    Ok(risk_assessment.as_compliance_entry())
+    Ok(risk_assessment.as_compliance_entry(risk_threshold))
 }
 
 /// Register a wallet with chainalysis
@@ -3,11 +3,14 @@
 use std::time::{Duration, SystemTime};
 
 use compliance_api::ComplianceStatus;
-use diesel::{ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl};
+use diesel::{
+    AsChangeset, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl,
+};
 use renegade_util::err_str;
 
 use crate::{
     error::ComplianceServerError,
+    provider::RiskLevel,
     schema::{
         wallet_compliance,
         wallet_compliance::dsl::{address as address_col, wallet_compliance as compliance_table},
@@ -22,7 +25,7 @@ const DEFAULT_EXPIRATION_DURATION: Duration = Duration::from_days(365);
 // ----------
 
 /// A compliance entry for a wallet
-#[derive(Debug, Clone, Queryable, Insertable)]
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
 #[table_name = "wallet_compliance"]
 #[allow(missing_docs)]
 pub struct ComplianceEntry {
@@ -32,22 +35,72 @@ pub struct ComplianceEntry {
     pub reason: String,
     pub created_at: SystemTime,
     pub expires_at: SystemTime,
+    pub screened_at: SystemTime,
+    pub max_exposure_pct: Option<f64>,
+    pub categories: Vec<String>,
 }
 
 impl ComplianceEntry {
     /// Create a new entry from a risk assessment
-    pub fn new(address: String, is_compliant: bool, risk_level: String, reason: String) -> Self {
+    ///
+    /// The address is lowercased so that entries are keyed case-insensitively
+    pub fn new(
+        address: String,
+        is_compliant: bool,
+        risk_level: String,
+        reason: String,
+        max_exposure_pct: Option<f64>,
+        categories: Vec<String>,
+    ) -> Self {
         let created_at = SystemTime::now();
         let expires_at = created_at + DEFAULT_EXPIRATION_DURATION;
-        ComplianceEntry { address, is_compliant, risk_level, reason, created_at, expires_at }
+        let screened_at = created_at;
+        ComplianceEntry {
+            address: address.to_lowercase(),
+            is_compliant,
+            risk_level,
+            reason,
+            created_at,
+            expires_at,
+            screened_at,
+            max_exposure_pct,
+            categories,
+        }
+    }
+
+    /// Whether the entry was screened within the given TTL
+    ///
+    /// Entries are always fresh if no TTL is given
+    pub fn is_fresh(&self, ttl: Option<Duration>) -> bool {
+        let ttl = match ttl {
+            Some(ttl) => ttl,
+            None => return true,
+        };
+
+        // Entries screened in the future, e.g. due to clock skew, are fresh
+        let age = self.screened_at.elapsed().unwrap_or_default();
+        age <= ttl
     }
 
-    /// Get the compliance status for an entry
-    pub fn compliance_status(&self) -> ComplianceStatus {
-        if self.is_compliant {
+    /// Get the compliance status for an entry under the given risk threshold
+    ///
+    /// Compliance is derived from the stored risk level so that threshold
+    /// changes apply to cached entries, falling back to the compliance recorded
+    /// at screening time if the risk level is not recognized
+    pub fn compliance_status(&self, risk_threshold: RiskLevel) -> ComplianceStatus {
+        let compliant = match RiskLevel::from_assessment(&self.risk_level) {
+            Some(level) => level < risk_threshold,
+            None => self.is_compliant,
+        };
+
+        if compliant {
             ComplianceStatus::Compliant
         } else {
-            ComplianceStatus::NotCompliant { reason: self.reason.clone() }
+            ComplianceStatus::NotCompliant {
+                reason: self.reason.clone(),
+                max_exposure_pct: self.max_exposure_pct,
+                categories: self.categories.clone(),
+            }
         }
     }
 }
@@ -69,13 +122,30 @@ pub fn get_compliance_entry(
     Ok(query.first().cloned())
 }
 
-/// Insert a compliance entry into the database
-pub fn insert_compliance_entry(
+/// Get the compliance entries for a set of addresses
+///
+/// Addresses without an entry are omitted from the result
+pub fn get_compliance_entries(
+    addresses: &[String],
+    conn: &mut PgConnection,
+) -> Result<Vec<ComplianceEntry>, ComplianceServerError> {
+    compliance_table
+        .filter(address_col.eq_any(addresses))
+        .load::<ComplianceEntry>(conn)
+        .map_err(err_str!(ComplianceServerError::Db))
+}
+
+/// Insert a compliance entry into the database, overwriting any existing entry
+/// for the address
+pub fn upsert_compliance_entry(
     entry: ComplianceEntry,
     conn: &mut PgConnection,
 ) -> Result<(), ComplianceServerError> {
     diesel::insert_into(compliance_table)
-        .values(entry)
+        .values(&entry)
+        .on_conflict(address_col)
+        .do_update()
+        .set(&entry)
         .execute(conn)
         .map_err(err_str!(ComplianceServerError::Db))?;
 
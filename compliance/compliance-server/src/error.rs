@@ -11,6 +11,10 @@ pub enum ComplianceServerError {
     Db(String),
     /// An error with the chainalysis API
     Chainalysis(String),
+    /// An invalid request
+    BadRequest(String),
+    /// An invalid server configuration
+    Config(String),
 }
 
 impl Display for ComplianceServerError {
@@ -18,6 +22,8 @@ impl Display for ComplianceServerError {
         match self {
             ComplianceServerError::Db(e) => write!(f, "Database error: {}", e),
             ComplianceServerError::Chainalysis(e) => write!(f, "Chainalysis error: {}", e),
+            ComplianceServerError::BadRequest(e) => write!(f, "Bad request: {}", e),
+            ComplianceServerError::Config(e) => write!(f, "Config error: {}", e),
         }
     }
 }
@@ -7,31 +7,39 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![feature(duration_constructors)]
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use chainalysis_api::query_chainalysis;
 use clap::Parser;
-use compliance_api::{ComplianceCheckResponse, ComplianceStatus};
-use db::insert_compliance_entry;
+use compliance_api::{
+    BatchComplianceCheckRequest, BatchComplianceCheckResponse, ComplianceCheckResponse,
+    ComplianceStatus, MAX_BATCH_SIZE,
+};
+use db::{get_compliance_entries, upsert_compliance_entry};
 use diesel::pg::PgConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
 use error::ComplianceServerError;
+use futures::{stream, StreamExt};
+use provider::{ProviderKind, RiskLevel, SharedComplianceProvider};
 use renegade_util::err_str;
 use renegade_util::telemetry::{setup_system_logger, LevelFilter};
-use tracing::info;
-use warp::{reply::Json, Filter};
+use tracing::{error, info};
+use warp::{http::StatusCode, reply::Json, Filter, Rejection, Reply};
 
 use crate::db::get_compliance_entry;
 
 pub mod chainalysis_api;
 pub mod db;
 pub mod error;
+pub mod provider;
 #[allow(missing_docs, clippy::missing_docs_in_private_items)]
 pub mod schema;
 
 /// The type of the connection pool
 type ConnectionPool = Arc<Pool<ConnectionManager<PgConnection>>>;
 
+/// The maximum number of concurrent provider queries made for a batch
+const MAX_CONCURRENT_BATCH_QUERIES: usize = 10;
+
 /// The CLI for the compliance server
 #[derive(Debug, Clone, Parser)]
 #[command(about = "The CLI for the compliance server")]
@@ -39,12 +47,26 @@ struct Cli {
     /// The port to listen on
     #[arg(short, long, default_value = "3000")]
     port: u16,
-    /// The Chainalysis API key
+    /// The compliance provider used to screen wallets
+    #[arg(long, env = "COMPLIANCE_PROVIDER", value_enum, default_value = "chainalysis")]
+    compliance_provider: ProviderKind,
+    /// The Chainalysis API key, required by the chainalysis provider
     #[arg(long, env = "CHAINALYSIS_API_KEY")]
-    chainalysis_api_key: String,
+    chainalysis_api_key: Option<String>,
+    /// The risk level at or above which a wallet is marked non-compliant
+    ///
+    /// Levels are ordered `low < medium < high < severe`
+    #[arg(long, env = "RISK_THRESHOLD", value_enum, default_value = "high")]
+    risk_threshold: RiskLevel,
     /// The url of the compliance database
     #[arg(long, env = "DATABASE_URL")]
     db_url: String,
+    /// The number of hours after which a cached compliance entry is
+    /// considered stale and the wallet is re-screened
+    ///
+    /// If unset, cached entries never go stale
+    #[arg(long, env = "COMPLIANCE_TTL_HOURS")]
+    compliance_ttl_hours: Option<u64>,
 }
 
 #[tokio::main]
@@ -56,20 +78,40 @@ async fn main() {
     let manager = ConnectionManager::<PgConnection>::new(cli.db_url.clone());
     let pool = Pool::builder().build(manager).expect("Failed to create pool");
     let pool = Arc::new(pool);
+    let ttl = cli.compliance_ttl_hours.map(Duration::from_hours);
+    let risk_threshold = cli.risk_threshold;
+
+    // Build the compliance provider
+    let provider = cli
+        .compliance_provider
+        .build(cli.chainalysis_api_key.clone(), cli.risk_threshold)
+        .expect("Failed to build compliance provider");
 
     // Get compliance information for a wallet
-    let chainalysis_key = cli.chainalysis_api_key.clone();
+    let provider_clone = provider.clone();
+    let pool_clone = pool.clone();
     let compliance_check = warp::get()
         .and(warp::path("v0"))
         .and(warp::path("check-compliance"))
         .and(warp::path::param::<String>()) // wallet_address
         .and_then(move |wallet_address| {
-            let key = chainalysis_key.clone();
+            let provider = provider_clone.clone();
+            let pool = pool_clone.clone();
+
+            async move { handle_req(wallet_address, &provider, ttl, risk_threshold, pool).await }
+        });
+
+    // Get compliance information for a batch of wallets
+    let batch_compliance_check = warp::post()
+        .and(warp::path("v0"))
+        .and(warp::path("check-compliance-batch"))
+        .and(warp::path::end())
+        .and(warp::body::json::<BatchComplianceCheckRequest>())
+        .and_then(move |req| {
+            let provider = provider.clone();
             let pool = pool.clone();
 
-            async move {
-                handle_req(wallet_address, &key, pool).await
-            }
+            async move { handle_batch_req(req, &provider, ttl, risk_threshold, pool).await }
         });
 
     // GET /ping
@@ -77,40 +119,164 @@ async fn main() {
         .and(warp::path("ping"))
         .map(|| warp::reply::with_status("PONG", warp::http::StatusCode::OK));
 
-    let routes = compliance_check.or(ping);
+    let routes = compliance_check.or(batch_compliance_check).or(ping).recover(handle_rejection);
     warp::serve(routes).run(([0, 0, 0, 0], cli.port)).await
 }
 
 /// Handle a request for a compliance check
 async fn handle_req(
     wallet_address: String,
-    chainalysis_api_key: &str,
+    provider: &SharedComplianceProvider,
+    ttl: Option<Duration>,
+    risk_threshold: RiskLevel,
     pool: ConnectionPool,
 ) -> Result<Json, warp::Rejection> {
     let compliance_status =
-        check_wallet_compliance(wallet_address, chainalysis_api_key, pool).await?;
+        check_wallet_compliance(wallet_address, provider, ttl, risk_threshold, pool).await?;
     let resp = ComplianceCheckResponse { compliance_status };
     Ok(warp::reply::json(&resp))
 }
 
+/// Handle a request for a batch compliance check
+async fn handle_batch_req(
+    req: BatchComplianceCheckRequest,
+    provider: &SharedComplianceProvider,
+    ttl: Option<Duration>,
+    risk_threshold: RiskLevel,
+    pool: ConnectionPool,
+) -> Result<Json, warp::Rejection> {
+    if req.addresses.is_empty() || req.addresses.len() > MAX_BATCH_SIZE {
+        let msg = format!("batch must contain between 1 and {MAX_BATCH_SIZE} addresses");
+        return Err(warp::reject::custom(ComplianceServerError::BadRequest(msg)));
+    }
+
+    let resp = check_batch_compliance(req.addresses, provider, ttl, risk_threshold, pool).await?;
+    Ok(warp::reply::json(&resp))
+}
+
+/// Handle a rejection from an endpoint handler
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    if let Some(e) = err.find::<ComplianceServerError>() {
+        // Internal errors are logged rather than returned to the client
+        let (msg, code) = match e {
+            ComplianceServerError::BadRequest(_) => (e.to_string(), StatusCode::BAD_REQUEST),
+            _ => {
+                error!("Error handling request: {e}");
+                ("Internal server error".to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            },
+        };
+
+        Ok(warp::reply::with_status(msg, code))
+    } else {
+        Err(err)
+    }
+}
+
 /// Check the compliance of a wallet
+///
+/// Cached entries older than the TTL are bypassed and the wallet is
+/// re-screened, compliance is evaluated against the current risk threshold.
+/// Addresses are lowercased so that differently cased inputs share an entry
 async fn check_wallet_compliance(
     wallet_address: String,
-    chainalysis_api_key: &str,
+    provider: &SharedComplianceProvider,
+    ttl: Option<Duration>,
+    risk_threshold: RiskLevel,
     pool: ConnectionPool,
 ) -> Result<ComplianceStatus, ComplianceServerError> {
-    // 1. Check the DB first
+    let wallet_address = wallet_address.to_lowercase();
+
+    // 1. Check the DB first, releasing the connection before querying the provider
     let mut conn = pool.get().map_err(err_str!(ComplianceServerError::Db))?;
     let compliance_entry = get_compliance_entry(&wallet_address, &mut conn)?;
-    if let Some(compliance_entry) = compliance_entry {
-        return Ok(compliance_entry.compliance_status());
+    drop(conn);
+
+    match compliance_entry {
+        Some(entry) if entry.is_fresh(ttl) => return Ok(entry.compliance_status(risk_threshold)),
+        Some(_) => info!("cached entry is stale, re-screening with provider"),
+        None => info!("address not cached in DB, querying provider"),
     }
 
-    // 2. If not present, check the chainalysis API
-    info!("address not cached in DB, querying Chainalysis");
-    let compliance_entry = query_chainalysis(&wallet_address, chainalysis_api_key).await?;
+    // 2. If not present or stale, screen the wallet with the provider
+    let compliance_entry = provider.screen(&wallet_address).await?;
 
     // 3. Cache in the DB
-    insert_compliance_entry(compliance_entry.clone(), &mut conn)?;
-    Ok(compliance_entry.compliance_status())
+    let mut conn = pool.get().map_err(err_str!(ComplianceServerError::Db))?;
+    upsert_compliance_entry(compliance_entry.clone(), &mut conn)?;
+    Ok(compliance_entry.compliance_status(risk_threshold))
+}
+
+/// Check the compliance of a batch of wallets
+///
+/// Cached entries are read from the DB in a single query, and the provider is
+/// queried concurrently for the remaining addresses and those with stale
+/// entries. A failure to screen one address does not fail the batch, it is
+/// instead reported for that address
+async fn check_batch_compliance(
+    wallet_addresses: Vec<String>,
+    provider: &SharedComplianceProvider,
+    ttl: Option<Duration>,
+    risk_threshold: RiskLevel,
+    pool: ConnectionPool,
+) -> Result<BatchComplianceCheckResponse, ComplianceServerError> {
+    let mut addresses: Vec<String> =
+        wallet_addresses.iter().map(|addr| addr.to_lowercase()).collect();
+    addresses.sort();
+    addresses.dedup();
+
+    // 1. Check the DB first, releasing the connection before querying the provider
+    let mut conn = pool.get().map_err(err_str!(ComplianceServerError::Db))?;
+    let cached_entries = get_compliance_entries(&addresses, &mut conn)?;
+    drop(conn);
+
+    let mut statuses: HashMap<String, ComplianceStatus> = cached_entries
+        .into_iter()
+        .filter(|entry| entry.is_fresh(ttl))
+        .map(|entry| (entry.address.to_lowercase(), entry.compliance_status(risk_threshold)))
+        .collect();
+
+    // 2. Screen the misses with the provider
+    let misses: Vec<&String> =
+        addresses.iter().filter(|addr| !statuses.contains_key(*addr)).collect();
+    info!("{} addresses not cached in DB, querying provider", misses.len());
+    let provider = provider.as_ref();
+    let results: Vec<_> = stream::iter(misses)
+        .map(|addr| async move { (addr, provider.screen(addr).await) })
+        .buffer_unordered(MAX_CONCURRENT_BATCH_QUERIES)
+        .collect()
+        .await;
+
+    // 3. Cache the successful queries in the DB, recording any failures
+    let mut conn = pool.get().map_err(err_str!(ComplianceServerError::Db))?;
+    let mut errors: HashMap<String, String> = HashMap::new();
+    for (addr, res) in results {
+        match res {
+            Ok(entry) => {
+                statuses.insert(addr.clone(), entry.compliance_status(risk_threshold));
+                if let Err(e) = upsert_compliance_entry(entry, &mut conn) {
+                    error!("Failed to cache compliance entry for {addr}: {e}");
+                }
+            },
+            Err(e) => {
+                error!("Failed to screen {addr}: {e}");
+                errors.insert(addr.clone(), "failed to screen address".to_string());
+            },
+        }
+    }
+
+    // 4. Key the results by the addresses as given in the request
+    let mut resp = BatchComplianceCheckResponse {
+        compliance_statuses: HashMap::new(),
+        errors: HashMap::new(),
+    };
+    for addr in wallet_addresses {
+        let key = addr.to_lowercase();
+        if let Some(status) = statuses.get(&key) {
+            resp.compliance_statuses.insert(addr, status.clone());
+        } else if let Some(err) = errors.get(&key) {
+            resp.errors.insert(addr, err.clone());
+        }
+    }
+
+    Ok(resp)
 }
@@ -0,0 +1,81 @@
+//! Defines the abstraction over the compliance screening providers
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+
+use crate::{
+    chainalysis_api::ChainalysisProvider, db::ComplianceEntry, error::ComplianceServerError,
+};
+
+/// A shared, dynamically dispatched compliance provider
+pub type SharedComplianceProvider = Arc<dyn ComplianceProvider>;
+
+/// A provider that screens wallet addresses for compliance
+#[async_trait]
+pub trait ComplianceProvider: Send + Sync {
+    /// Screen the given address, returning a compliance entry to be cached
+    async fn screen(&self, address: &str) -> Result<ComplianceEntry, ComplianceServerError>;
+}
+
+/// A graded risk level assigned to an address by a provider
+///
+/// Levels are ordered from least to most risky:
+/// `Low < Medium < High < Severe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum RiskLevel {
+    /// Low risk
+    Low,
+    /// Medium risk
+    Medium,
+    /// High risk
+    High,
+    /// Severe risk
+    Severe,
+}
+
+impl RiskLevel {
+    /// Parse a risk level from a provider's assessment string
+    ///
+    /// Returns `None` if the assessment is not a known risk level
+    pub fn from_assessment(assessment: &str) -> Option<Self> {
+        match assessment.to_lowercase().as_str() {
+            "low" => Some(RiskLevel::Low),
+            "medium" => Some(RiskLevel::Medium),
+            "high" => Some(RiskLevel::High),
+            "severe" => Some(RiskLevel::Severe),
+            _ => None,
+        }
+    }
+}
+
+/// The compliance providers that may be selected at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProviderKind {
+    /// The Chainalysis entities API
+    Chainalysis,
+}
+
+impl ProviderKind {
+    /// Build the selected provider
+    ///
+    /// Errors if the configuration required by the provider is missing
+    pub fn build(
+        self,
+        chainalysis_api_key: Option<String>,
+        risk_threshold: RiskLevel,
+    ) -> Result<SharedComplianceProvider, ComplianceServerError> {
+        match self {
+            ProviderKind::Chainalysis => {
+                let api_key = chainalysis_api_key.ok_or_else(|| {
+                    ComplianceServerError::Config(
+                        "CHAINALYSIS_API_KEY must be set for the chainalysis provider".to_string(),
+                    )
+                })?;
+
+                Ok(Arc::new(ChainalysisProvider::new(api_key, risk_threshold)))
+            },
+        }
+    }
+}
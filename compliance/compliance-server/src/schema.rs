@@ -8,5 +8,8 @@ diesel::table! {
         reason -> Text,
         created_at -> Timestamp,
         expires_at -> Timestamp,
+        screened_at -> Timestamp,
+        max_exposure_pct -> Nullable<Float8>,
+        categories -> Array<Text>,
     }
 }
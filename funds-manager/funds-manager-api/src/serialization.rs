@@ -1,5 +1,9 @@
 //! Serialization helpers for the funds manager API
 
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::WithdrawAmount;
+
 /// A module for serializing and deserializing addresses as strings
 pub(crate) mod address_string_serialization {
     use std::str::FromStr;
@@ -36,6 +40,22 @@ pub(crate) mod u256_string_serialization {
     }
 }
 
+/// A module for serializing and deserializing u128 as strings
+pub(crate) mod u128_string_serialization {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    /// Serialize a u128 to a string
+    pub fn serialize<S: Serializer>(value: &u128, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.to_string())
+    }
+
+    /// Deserialize a string to a u128
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u128, D::Error> {
+        let s = String::deserialize(d)?;
+        s.parse::<u128>().map_err(|_| D::Error::custom("Invalid u128 value"))
+    }
+}
+
 /// A module for serializing and deserializing bytes from a hex string
 pub(crate) mod bytes_string_serialization {
     use ethers::types::Bytes;
@@ -55,11 +75,47 @@ pub(crate) mod bytes_string_serialization {
     }
 }
 
+/// The string sentinel used to request a withdrawal of the full balance
+const MAX_WITHDRAW_AMOUNT_SENTINEL: &str = "max";
+
+impl Serialize for WithdrawAmount {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            WithdrawAmount::Amount(amount) => s.serialize_f64(*amount),
+            WithdrawAmount::Max => s.serialize_str(MAX_WITHDRAW_AMOUNT_SENTINEL),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WithdrawAmount {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        /// The raw representation of a withdrawal amount
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawAmount {
+            /// A numeric amount
+            Amount(f64),
+            /// A string sentinel
+            Sentinel(String),
+        }
+
+        match RawAmount::deserialize(d)? {
+            RawAmount::Amount(amount) => Ok(WithdrawAmount::Amount(amount)),
+            RawAmount::Sentinel(s) if s.eq_ignore_ascii_case(MAX_WITHDRAW_AMOUNT_SENTINEL) => {
+                Ok(WithdrawAmount::Max)
+            },
+            RawAmount::Sentinel(s) => Err(D::Error::custom(format!("Invalid amount: {s}"))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ethers::types::{Address, Bytes, U256};
     use rand::{thread_rng, Rng};
 
+    use crate::WithdrawAmount;
+
     /// Test serialization and deserialization of an address
     #[test]
     fn test_address_serialization() {
@@ -93,4 +149,17 @@ mod tests {
         let deserialized: Bytes = serde_json::from_str(&serialized).unwrap();
         assert_eq!(bytes, deserialized);
     }
+
+    /// Test serialization and deserialization of withdrawal amounts
+    #[test]
+    fn test_withdraw_amount_serialization() {
+        for amount in [WithdrawAmount::Amount(1.5), WithdrawAmount::Max] {
+            let serialized = serde_json::to_string(&amount).unwrap();
+            let deserialized: WithdrawAmount = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(amount, deserialized);
+        }
+
+        assert_eq!(serde_json::to_string(&WithdrawAmount::Max).unwrap(), "\"max\"");
+        assert!(serde_json::from_str::<WithdrawAmount>("\"all\"").is_err());
+    }
 }
@@ -1,6 +1,8 @@
 //! API types for gas funding and gas wallet tracking
 use serde::{Deserialize, Serialize};
 
+use crate::WithdrawAmount;
+
 // --------------
 // | Api Routes |
 // --------------
@@ -22,7 +24,7 @@ pub const REPORT_ACTIVE_PEERS_ROUTE: &str = "report-active-peers";
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WithdrawGasRequest {
     /// The amount of gas to withdraw
-    pub amount: f64,
+    pub amount: WithdrawAmount,
     /// The address to withdraw to
     pub destination_address: String,
 }
@@ -32,6 +34,10 @@ pub struct WithdrawGasRequest {
 pub struct RefillGasRequest {
     /// The amount of gas to top up each wallet to
     pub amount: f64,
+    /// An optional idempotency key, a repeated request with the same key
+    /// returns the prior result rather than refilling again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 /// The response containing the gas wallet's address
@@ -11,6 +11,12 @@ use uuid::Uuid;
 pub const TRANSFER_TO_VAULT_ROUTE: &str = "transfer-to-vault";
 /// The route to withdraw funds from a hot wallet to Fireblocks
 pub const WITHDRAW_TO_HOT_WALLET_ROUTE: &str = "withdraw-to-hot-wallet";
+/// The route to set the daily withdrawal limit of a hot wallet
+pub const SET_WITHDRAWAL_LIMIT_ROUTE: &str = "withdrawal-limit";
+/// The route to wrap native ETH held by a hot wallet into WETH
+pub const WRAP_ETHER_ROUTE: &str = "wrap";
+/// The route to unwrap WETH held by a hot wallet into native ETH
+pub const UNWRAP_ETHER_ROUTE: &str = "unwrap";
 
 // -------------
 // | Api Types |
@@ -37,6 +43,9 @@ pub struct CreateHotWalletResponse {
 pub struct HotWalletBalancesResponse {
     /// The list of hot wallets with their balances
     pub wallets: Vec<WalletWithBalances>,
+    /// The total USD value of all hot wallets, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_usd: Option<f64>,
 }
 
 /// A hot wallet with its balances
@@ -46,6 +55,9 @@ pub struct WalletWithBalances {
     pub address: String,
     /// The balances of various tokens
     pub balances: Vec<TokenBalance>,
+    /// The total USD value of the wallet's priced balances, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_usd: Option<f64>,
 }
 
 /// A balance for a specific token
@@ -55,6 +67,12 @@ pub struct TokenBalance {
     pub mint: String,
     /// The balance amount
     pub amount: u128,
+    /// The USD value of the balance
+    ///
+    /// Only populated when USD valuations are requested, and `None` if no
+    /// price is available for the token
+    #[serde(default)]
+    pub usd_value: Option<f64>,
 }
 
 /// The request body for transferring funds from a hot wallet to its backing
@@ -79,3 +97,27 @@ pub struct WithdrawToHotWalletRequest {
     /// The amount to transfer
     pub amount: f64,
 }
+
+/// The request body for wrapping or unwrapping ETH held by a hot wallet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WrapEtherRequest {
+    /// The address of the hot wallet
+    pub hot_wallet_address: String,
+    /// The amount of ETH to wrap or unwrap
+    pub amount: f64,
+}
+
+/// The request body for setting the daily withdrawal limit of a hot wallet
+///
+/// The limit applies to funds moved into the hot wallet from its vault and to
+/// withdrawals out of the quoter hot wallet. Transfers back to the vault and
+/// gas withdrawals are not counted. Withdrawals of tokens that cannot be priced
+/// are rejected while a limit is set
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetWithdrawalLimitRequest {
+    /// The address of the hot wallet
+    pub hot_wallet_address: String,
+    /// The maximum USD value that may be withdrawn from the wallet in a
+    /// rolling 24 hour window, or `None` to remove the limit
+    pub daily_limit_usd: Option<f64>,
+}
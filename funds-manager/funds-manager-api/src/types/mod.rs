@@ -4,6 +4,19 @@ pub mod fees;
 pub mod gas;
 pub mod hot_wallets;
 pub mod quoters;
+pub mod vaults;
 
 /// The ping route
 pub const PING_ROUTE: &str = "ping";
+
+/// The amount of a withdrawal
+///
+/// Serialized as a number, or as the string `"max"` to withdraw the full
+/// balance at execution time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WithdrawAmount {
+    /// An explicit amount, in whole units of the asset
+    Amount(f64),
+    /// The full balance of the withdrawing wallet
+    Max,
+}
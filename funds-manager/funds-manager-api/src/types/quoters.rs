@@ -1,10 +1,13 @@
 //! API types for quoter management
 use ethers::types::{Address, Bytes, U256};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::serialization::{
-    address_string_serialization, bytes_string_serialization, u256_string_serialization,
+    address_string_serialization, bytes_string_serialization, u128_string_serialization,
+    u256_string_serialization,
 };
+use crate::WithdrawAmount;
 
 // --------------
 // | Api Routes |
@@ -18,6 +21,10 @@ pub const WITHDRAW_CUSTODY_ROUTE: &str = "withdraw";
 pub const GET_EXECUTION_QUOTE_ROUTE: &str = "get-execution-quote";
 /// The route to execute a swap on the quoter hot wallet
 pub const EXECUTE_SWAP_ROUTE: &str = "execute-swap";
+/// The route to list executed swaps
+pub const LIST_SWAPS_ROUTE: &str = "swaps";
+/// The route to estimate the gas cost of a swap without executing it
+pub const ESTIMATE_SWAP_GAS_ROUTE: &str = "estimate-gas";
 
 // -------------
 // | Api Types |
@@ -36,7 +43,7 @@ pub struct WithdrawFundsRequest {
     /// The mint of the asset to withdraw
     pub mint: String,
     /// The amount of funds to withdraw
-    pub amount: f64,
+    pub amount: WithdrawAmount,
     /// The address to withdraw to
     pub address: String,
 }
@@ -111,4 +118,70 @@ pub struct ExecuteSwapRequest {
 pub struct ExecuteSwapResponse {
     /// The tx hash of the swap
     pub tx_hash: String,
+    /// Whether the swap was skipped because the server is in dry-run mode, in
+    /// which case the tx hash is zero
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// The query params for estimating the gas cost of a swap
+///
+/// A quote is fetched for these params and its swap tx is estimated, but not
+/// executed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EstimateSwapGasRequest {
+    /// The token address we're buying
+    #[serde(with = "address_string_serialization")]
+    pub buy_token_address: Address,
+    /// The token address we're selling
+    #[serde(with = "address_string_serialization")]
+    pub sell_token_address: Address,
+    /// The amount of tokens to sell
+    #[serde(with = "u128_string_serialization")]
+    pub sell_amount: u128,
+}
+
+/// The response body for estimating the gas cost of a swap
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EstimateSwapGasResponse {
+    /// The estimated gas units used by the swap
+    pub estimated_gas: u64,
+    /// The estimated cost of the swap in ETH, at the current base fee
+    pub cost_eth: f64,
+    /// The estimated cost of the swap in USD, if an ETH price is available
+    pub cost_usd: Option<f64>,
+}
+
+/// A swap executed on the quoter hot wallet
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwapRecord {
+    /// The id of the swap
+    pub id: Uuid,
+    /// The venue the swap was executed on
+    pub venue: String,
+    /// The vault whose hot wallet executed the swap
+    pub source: String,
+    /// The token address sold
+    pub sell_token: String,
+    /// The token address bought
+    pub buy_token: String,
+    /// The amount of the sell token sold, in base units
+    pub sell_amount: String,
+    /// The price quoted for the swap
+    pub quoted_price: String,
+    /// The amount of the buy token received, in base units
+    pub buy_amount_actual: String,
+    /// The gas cost of the swap in wei
+    pub gas_cost: String,
+    /// The tx hash of the swap
+    pub tx_hash: String,
+    /// The time the swap was recorded, in milliseconds since the epoch
+    pub timestamp: u64,
+}
+
+/// The response body for listing executed swaps
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSwapsResponse {
+    /// The page of swaps, most recent first
+    pub swaps: Vec<SwapRecord>,
 }
@@ -0,0 +1,54 @@
+//! API types for managing Fireblocks vaults
+
+use serde::{Deserialize, Serialize};
+
+// --------------
+// | Api Routes |
+// --------------
+
+/// The route to transfer funds directly between two vaults
+pub const TRANSFER_BETWEEN_VAULTS_ROUTE: &str = "transfer";
+
+// -------------
+// | Api Types |
+// -------------
+
+/// The response containing the balances of all known vaults
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultBalancesResponse {
+    /// The vaults managed by the funds manager, with their balances
+    pub vaults: Vec<VaultWithBalances>,
+}
+
+/// A Fireblocks vault with its balances
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultWithBalances {
+    /// The name of the vault
+    pub name: String,
+    /// The balances of the assets held in the vault
+    pub balances: Vec<VaultAssetBalance>,
+}
+
+/// The balance of a single asset in a vault
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultAssetBalance {
+    /// The Fireblocks asset ID
+    pub asset_id: String,
+    /// The total balance of the asset
+    pub total: f64,
+    /// The balance of the asset available for transfer
+    pub available: f64,
+}
+
+/// The request body for transferring funds between two vaults
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VaultTransferRequest {
+    /// The name of the vault to transfer from
+    pub source_vault: String,
+    /// The name of the vault to transfer to
+    pub destination_vault: String,
+    /// The mint of the asset to transfer
+    pub mint: String,
+    /// The amount to transfer
+    pub amount: f64,
+}
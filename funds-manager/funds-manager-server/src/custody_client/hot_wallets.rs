@@ -6,21 +6,25 @@
 use std::{str::FromStr, sync::Arc};
 
 use ethers::{
+    middleware::SignerMiddleware,
     providers::{Http, Provider},
     signers::{LocalWallet, Signer},
-    types::Address,
-    utils::hex::ToHexExt,
+    types::{Address, TransactionReceipt, U256},
+    utils::{hex::ToHexExt, parse_units},
 };
 use funds_manager_api::hot_wallets::{TokenBalance, WalletWithBalances};
 use rand::thread_rng;
+use renegade_common::types::token::Token;
 use tracing::info;
 use uuid::Uuid;
 
-use super::CustodyClient;
+use super::{CustodyClient, WETH_TICKER};
 use crate::{
     custody_client::DepositWithdrawSource,
     error::FundsManagerError,
-    helpers::{create_secrets_manager_entry_with_description, get_secret, ERC20},
+    helpers::{
+        create_secrets_manager_entry_with_description, dry_run_receipt, get_secret, ERC20, WETH,
+    },
 };
 
 impl CustodyClient {
@@ -73,10 +77,18 @@ impl CustodyClient {
             let mut balances = Vec::new();
             for mint in mints.iter() {
                 let balance = self.get_token_balance(&wallet, mint, provider.clone()).await?;
-                balances.push(TokenBalance { mint: mint.clone(), amount: balance });
+                balances.push(TokenBalance {
+                    mint: mint.clone(),
+                    amount: balance,
+                    usd_value: None,
+                });
             }
 
-            hot_wallet_balances.push(WalletWithBalances { address: wallet, balances });
+            hot_wallet_balances.push(WalletWithBalances {
+                address: wallet,
+                balances,
+                total_usd: None,
+            });
         }
 
         Ok(hot_wallet_balances)
@@ -121,10 +133,93 @@ impl CustodyClient {
         self.withdraw_from_fireblocks(source, mint, amount).await
     }
 
+    /// Wrap native ETH held by a hot wallet into WETH
+    pub async fn wrap_ether(
+        &self,
+        hot_wallet_address: &str,
+        amount: f64,
+    ) -> Result<TransactionReceipt, FundsManagerError> {
+        let weth = self.get_weth_contract(hot_wallet_address).await?;
+        let amount_units: U256 =
+            parse_units(amount.to_string(), "ether").map_err(FundsManagerError::parse)?.into();
+
+        if self.dry_run {
+            info!("Dry run: skipping wrap of {amount} ETH in hot wallet {hot_wallet_address}");
+            return Ok(dry_run_receipt());
+        }
+
+        let tx = weth.deposit().value(amount_units);
+        let pending_tx = tx.send().await.map_err(|e| {
+            FundsManagerError::arbitrum(format!("Failed to send transaction: {}", e))
+        })?;
+
+        let receipt = pending_tx
+            .await
+            .map_err(FundsManagerError::arbitrum)?
+            .ok_or_else(|| FundsManagerError::arbitrum("Transaction failed".to_string()))?;
+        info!(
+            "Wrapped {amount} ETH in hot wallet {hot_wallet_address}. \n\tTransaction hash: {:#x}",
+            receipt.transaction_hash
+        );
+
+        Ok(receipt)
+    }
+
+    /// Unwrap WETH held by a hot wallet into native ETH
+    pub async fn unwrap_ether(
+        &self,
+        hot_wallet_address: &str,
+        amount: f64,
+    ) -> Result<TransactionReceipt, FundsManagerError> {
+        let weth = self.get_weth_contract(hot_wallet_address).await?;
+        let amount_units: U256 =
+            parse_units(amount.to_string(), "ether").map_err(FundsManagerError::parse)?.into();
+
+        if self.dry_run {
+            info!("Dry run: skipping unwrap of {amount} WETH in hot wallet {hot_wallet_address}");
+            return Ok(dry_run_receipt());
+        }
+
+        let tx = weth.withdraw(amount_units);
+        let pending_tx = tx.send().await.map_err(|e| {
+            FundsManagerError::arbitrum(format!("Failed to send transaction: {}", e))
+        })?;
+
+        let receipt = pending_tx
+            .await
+            .map_err(FundsManagerError::arbitrum)?
+            .ok_or_else(|| FundsManagerError::arbitrum("Transaction failed".to_string()))?;
+        info!(
+            "Unwrapped {amount} WETH in hot wallet {hot_wallet_address}. \n\tTransaction hash: {:#x}",
+            receipt.transaction_hash
+        );
+
+        Ok(receipt)
+    }
+
     // ------------
     // | Handlers |
     // ------------
 
+    /// Get the WETH contract, signing with the given hot wallet's key
+    async fn get_weth_contract(
+        &self,
+        hot_wallet_address: &str,
+    ) -> Result<WETH<SignerMiddleware<Provider<Http>, LocalWallet>>, FundsManagerError> {
+        let hot_wallet = self.get_hot_wallet_by_address(hot_wallet_address).await?;
+        let secret_value = get_secret(&hot_wallet.secret_id, &self.aws_config).await?;
+        let wallet = LocalWallet::from_str(&secret_value)
+            .map_err(FundsManagerError::parse)?
+            .with_chain_id(self.chain_id);
+
+        let provider = self.get_rpc_provider()?;
+        let client = SignerMiddleware::new(provider, wallet);
+        let weth_address = Address::from_str(&Token::from_ticker(WETH_TICKER).get_addr())
+            .map_err(FundsManagerError::parse)?;
+
+        Ok(WETH::new(weth_address, Arc::new(client)))
+    }
+
     /// The secret name for a hot wallet
     pub(crate) fn hot_wallet_secret_name(address: &str) -> String {
         format!("hot-wallet-{address}")
@@ -3,13 +3,14 @@ pub mod deposit;
 pub mod gas_wallets;
 mod hot_wallets;
 mod queries;
+mod vaults;
 pub mod withdraw;
 
 use aws_config::SdkConfig as AwsConfig;
 use ethers::middleware::SignerMiddleware;
 use ethers::providers::{Http, Middleware, Provider};
 use ethers::signers::{LocalWallet, Signer};
-use ethers::types::{Address, TransactionReceipt, TransactionRequest};
+use ethers::types::{Address, TransactionReceipt, TransactionRequest, U256};
 use ethers::utils::format_units;
 use fireblocks_sdk::types::Transaction;
 use fireblocks_sdk::{
@@ -18,13 +19,33 @@ use fireblocks_sdk::{
 };
 use renegade_util::err_str;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::info;
 
 use crate::db::{DbConn, DbPool};
 use crate::error::FundsManagerError;
-use crate::helpers::ERC20;
+use crate::helpers::{dry_run_receipt, ERC20};
+
+/// The ticker of the wrapped native asset
+pub(crate) const WETH_TICKER: &str = "WETH";
+/// The number of decimals of the native asset
+pub(crate) const ETHER_DECIMALS: u32 = 18;
+
+/// Convert an amount in a token's base units into a float in whole units
+pub(crate) fn units_to_f64(amount: U256, decimals: u32) -> Result<f64, FundsManagerError> {
+    let amount_str = format_units(amount, decimals).map_err(FundsManagerError::parse)?;
+    amount_str.parse::<f64>().map_err(FundsManagerError::parse)
+}
+
+/// The configuration for polling Fireblocks transactions
+#[derive(Clone, Copy, Debug)]
+pub struct FireblocksPollConfig {
+    /// The time to wait for a transaction to complete before giving up
+    pub timeout: Duration,
+    /// The interval at which to poll the transaction's status
+    pub interval: Duration,
+}
 
 /// The source of a deposit
 #[derive(Clone, Copy)]
@@ -38,6 +59,9 @@ pub(crate) enum DepositWithdrawSource {
 }
 
 impl DepositWithdrawSource {
+    /// All deposit sources, each of which is backed by a distinct vault
+    pub(crate) const ALL: [Self; 3] = [Self::Quoter, Self::FeeRedemption, Self::Gas];
+
     /// Get the Fireblocks vault name into which the given deposit source should
     /// deposit funds
     pub(crate) fn vault_name(&self) -> &str {
@@ -74,11 +98,16 @@ pub struct CustodyClient {
     db_pool: Arc<DbPool>,
     /// The AWS config
     aws_config: AwsConfig,
+    /// Whether to skip sending on-chain transfers, returning synthetic
+    /// receipts instead
+    dry_run: bool,
+    /// The configuration for polling Fireblocks transactions
+    poll_config: FireblocksPollConfig,
 }
 
 impl CustodyClient {
     /// Create a new CustodyClient
-    #[allow(clippy::needless_pass_by_value)]
+    #[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
     pub fn new(
         chain_id: u64,
         fireblocks_api_key: String,
@@ -86,6 +115,8 @@ impl CustodyClient {
         arbitrum_rpc_url: String,
         db_pool: Arc<DbPool>,
         aws_config: AwsConfig,
+        dry_run: bool,
+        poll_config: FireblocksPollConfig,
     ) -> Self {
         let fireblocks_api_secret = fireblocks_api_secret.as_bytes().to_vec();
         Self {
@@ -95,6 +126,8 @@ impl CustodyClient {
             arbitrum_rpc_url,
             db_pool,
             aws_config,
+            dry_run,
+            poll_config,
         }
     }
 
@@ -151,20 +184,34 @@ impl CustodyClient {
     }
 
     /// Poll a fireblocks transaction for completion
+    ///
+    /// If polling fails after the transaction has been observed, the
+    /// transaction is reported as still pending rather than failed, as it
+    /// may yet complete
     pub(crate) async fn poll_fireblocks_transaction(
         &self,
         transaction_id: &str,
     ) -> Result<Transaction, FundsManagerError> {
+        let config = self.poll_config;
         let client = self.get_fireblocks_client()?;
-        let timeout = Duration::from_secs(60);
-        let interval = Duration::from_secs(5);
-        client
-            .poll_transaction(transaction_id, timeout, interval, |tx| {
+        let last_status = Mutex::new(None);
+        let res = client
+            .poll_transaction(transaction_id, config.timeout, config.interval, |tx| {
                 info!("tx {}: {:?}", transaction_id, tx.status);
+                *last_status.lock().unwrap() = Some(format!("{:?}", tx.status));
             })
-            .await
-            .map_err(FundsManagerError::fireblocks)
-            .map(|(tx, _rid)| tx)
+            .await;
+
+        match res {
+            Ok((tx, _rid)) => Ok(tx),
+            Err(e) => match last_status.into_inner().unwrap() {
+                Some(status) => Err(FundsManagerError::fireblocks(format!(
+                    "tx {transaction_id} still pending after {:?} (last status: {status}): {e}",
+                    config.timeout
+                ))),
+                None => Err(FundsManagerError::fireblocks(e)),
+            },
+        }
     }
 
     // --- Arbitrum JSON RPC --- //
@@ -177,15 +224,19 @@ impl CustodyClient {
 
     /// Get the native token balance of an address
     pub(crate) async fn get_ether_balance(&self, address: &str) -> Result<f64, FundsManagerError> {
+        let balance = self.get_ether_balance_wei(address).await?;
+        units_to_f64(balance, ETHER_DECIMALS)
+    }
+
+    /// Get the native token balance of an address in wei
+    pub(crate) async fn get_ether_balance_wei(
+        &self,
+        address: &str,
+    ) -> Result<U256, FundsManagerError> {
         let provider = self.get_rpc_provider()?;
         let client = Arc::new(provider);
         let address = Address::from_str(address).map_err(FundsManagerError::parse)?;
-        let balance =
-            client.get_balance(address, None).await.map_err(FundsManagerError::arbitrum)?;
-
-        // Convert U256 to f64
-        let balance_str = format_units(balance, "ether").map_err(FundsManagerError::parse)?;
-        balance_str.parse::<f64>().map_err(FundsManagerError::parse)
+        client.get_balance(address, None).await.map_err(FundsManagerError::arbitrum)
     }
 
     /// Transfer ether from the given wallet
@@ -194,17 +245,31 @@ impl CustodyClient {
         to: &str,
         amount: f64,
         wallet: LocalWallet,
+    ) -> Result<TransactionReceipt, FundsManagerError> {
+        let amount_units = ethers::utils::parse_units(amount.to_string(), "ether")
+            .map_err(FundsManagerError::parse)?;
+        self.transfer_ether_wei(to, amount_units.into(), wallet).await
+    }
+
+    /// Transfer the given amount of wei from the given wallet
+    pub(crate) async fn transfer_ether_wei(
+        &self,
+        to: &str,
+        amount: U256,
+        wallet: LocalWallet,
     ) -> Result<TransactionReceipt, FundsManagerError> {
         let wallet = wallet.with_chain_id(self.chain_id);
         let provider = self.get_rpc_provider()?;
         let client = SignerMiddleware::new(provider, wallet);
 
         let to = Address::from_str(to).map_err(FundsManagerError::parse)?;
-        let amount_units = ethers::utils::parse_units(amount.to_string(), "ether")
-            .map_err(FundsManagerError::parse)?;
+        if self.dry_run {
+            info!("Dry run: skipping transfer of {amount} wei to {to:#x}");
+            return Ok(dry_run_receipt());
+        }
 
-        info!("Transferring {amount} ETH to {to:#x}");
-        let tx = TransactionRequest::new().to(to).value(amount_units);
+        info!("Transferring {amount} wei to {to:#x}");
+        let tx = TransactionRequest::new().to(to).value(amount);
         let pending_tx =
             client.send_transaction(tx, None).await.map_err(FundsManagerError::arbitrum)?;
         pending_tx
@@ -219,6 +284,17 @@ impl CustodyClient {
         token_address: &str,
         address: &str,
     ) -> Result<f64, FundsManagerError> {
+        let (balance, decimals) = self.get_erc20_balance_units(token_address, address).await?;
+        units_to_f64(balance, decimals)
+    }
+
+    /// Get the erc20 balance of an address in the token's base units, along
+    /// with the token's decimals
+    pub(crate) async fn get_erc20_balance_units(
+        &self,
+        token_address: &str,
+        address: &str,
+    ) -> Result<(U256, u32), FundsManagerError> {
         // Setup the provider
         let token_address = Address::from_str(token_address).map_err(FundsManagerError::parse)?;
         let address = Address::from_str(address).map_err(FundsManagerError::parse)?;
@@ -226,14 +302,22 @@ impl CustodyClient {
         let client = Arc::new(provider);
         let erc20 = ERC20::new(token_address, client);
 
-        // Fetch the balance and correct for the ERC20 decimal precision
         let decimals = erc20.decimals().call().await.map_err(FundsManagerError::arbitrum)? as u32;
         let balance =
             erc20.balance_of(address).call().await.map_err(FundsManagerError::arbitrum)?;
-        let bal_str = format_units(balance, decimals).map_err(FundsManagerError::parse)?;
-        let bal_f64 = bal_str.parse::<f64>().map_err(FundsManagerError::parse)?;
+        Ok((balance, decimals))
+    }
 
-        Ok(bal_f64)
+    /// Get the decimals of an erc20 token
+    pub(crate) async fn get_erc20_decimals(
+        &self,
+        token_address: &str,
+    ) -> Result<u32, FundsManagerError> {
+        let token_address = Address::from_str(token_address).map_err(FundsManagerError::parse)?;
+        let provider = self.get_rpc_provider()?;
+        let erc20 = ERC20::new(token_address, Arc::new(provider));
+        let decimals = erc20.decimals().call().await.map_err(FundsManagerError::arbitrum)?;
+        Ok(decimals as u32)
     }
 
     /// Perform an erc20 transfer
@@ -243,6 +327,21 @@ impl CustodyClient {
         to_address: &str,
         amount: f64,
         wallet: LocalWallet,
+    ) -> Result<TransactionReceipt, FundsManagerError> {
+        // Convert the amount using the token's decimals
+        let decimals = self.get_erc20_decimals(mint).await?;
+        let amount = ethers::utils::parse_units(amount.to_string(), decimals)
+            .map_err(FundsManagerError::parse)?;
+        self.erc20_transfer_units(mint, to_address, amount.into(), wallet).await
+    }
+
+    /// Perform an erc20 transfer of the given amount in the token's base units
+    pub(crate) async fn erc20_transfer_units(
+        &self,
+        mint: &str,
+        to_address: &str,
+        amount: U256,
+        wallet: LocalWallet,
     ) -> Result<TransactionReceipt, FundsManagerError> {
         // Set the chain ID
         let wallet = wallet.with_chain_id(self.chain_id);
@@ -253,14 +352,13 @@ impl CustodyClient {
         let token_address = Address::from_str(mint).map_err(FundsManagerError::parse)?;
         let token = ERC20::new(token_address, Arc::new(client));
 
-        // Convert the amount using the token's decimals
-        let decimals = token.decimals().call().await.map_err(FundsManagerError::arbitrum)? as u32;
-        let amount = ethers::utils::parse_units(amount.to_string(), decimals)
-            .map_err(FundsManagerError::parse)?
-            .into();
-
         // Transfer the tokens
         let to_address = Address::from_str(to_address).map_err(FundsManagerError::parse)?;
+        if self.dry_run {
+            info!("Dry run: skipping transfer of {amount} {mint} to {to_address:#x}");
+            return Ok(dry_run_receipt());
+        }
+
         let tx = token.transfer(to_address, amount);
         let pending_tx = tx.send().await.map_err(|e| {
             FundsManagerError::arbitrum(format!("Failed to send transaction: {}", e))
@@ -1,17 +1,28 @@
 //! Queries for managing custody data
 
-use diesel::{ExpressionMethods, QueryDsl};
-use diesel_async::RunQueryDsl;
+use std::time::SystemTime;
+
+use diesel::{
+    dsl::{exists, sum},
+    result::Error as DieselError,
+    ExpressionMethods, OptionalExtension, QueryDsl,
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use renegade_util::err_str;
 use tracing::info;
 use uuid::Uuid;
 
-use crate::db::models::{GasWallet, GasWalletStatus, HotWallet};
-use crate::db::schema::gas_wallets;
+use crate::db::models::{
+    GasRefill, GasWallet, GasWalletStatus, HotWallet, Swap, Withdrawal, WithdrawalLimit,
+};
 use crate::db::schema::hot_wallets;
+use crate::db::schema::{gas_refills, gas_wallets};
+use crate::db::schema::{swaps, withdrawal_limits, withdrawals};
 use crate::error::FundsManagerError;
 use crate::CustodyClient;
 
+use super::withdraw::{withdrawal_window_start, within_withdrawal_limit, WithdrawalReservation};
+
 impl CustodyClient {
     // ---------------
     // | Gas Wallets |
@@ -146,6 +157,15 @@ impl CustodyClient {
             .map_err(err_str!(FundsManagerError::Db))
     }
 
+    /// Check whether a hot wallet exists with the given address
+    pub async fn hot_wallet_exists(&self, address: &str) -> Result<bool, FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::select(exists(hot_wallets::table.filter(hot_wallets::address.eq(address))))
+            .get_result::<bool>(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))
+    }
+
     /// Get a hot wallet for the given vault
     pub async fn get_hot_wallet_by_vault(
         &self,
@@ -184,4 +204,225 @@ impl CustodyClient {
 
         Ok(())
     }
+
+    // ---------------------
+    // | Withdrawal Limits |
+    // ---------------------
+
+    // --- Setters --- //
+
+    /// Set the daily withdrawal limit for a hot wallet, overwriting any
+    /// existing limit
+    pub async fn set_withdrawal_limit(
+        &self,
+        wallet_address: &str,
+        daily_limit_usd: f64,
+    ) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        let entry = WithdrawalLimit { wallet_address: wallet_address.to_string(), daily_limit_usd };
+        diesel::insert_into(withdrawal_limits::table)
+            .values(&entry)
+            .on_conflict(withdrawal_limits::wallet_address)
+            .do_update()
+            .set(&entry)
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
+
+    /// Remove the daily withdrawal limit for a hot wallet
+    pub async fn remove_withdrawal_limit(
+        &self,
+        wallet_address: &str,
+    ) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::delete(
+            withdrawal_limits::table.filter(withdrawal_limits::wallet_address.eq(wallet_address)),
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
+
+    /// Reserve a withdrawal from a hot wallet against its daily withdrawal
+    /// limit
+    ///
+    /// The limit is checked and the withdrawal recorded in a single
+    /// transaction, holding a lock on the wallet's limit so that concurrent
+    /// withdrawals from the same wallet cannot jointly exceed it. The
+    /// withdrawal is recorded before the transfer executes, and must be
+    /// released if the transfer fails
+    pub async fn reserve_withdrawal(
+        &self,
+        wallet_address: &str,
+        mint: &str,
+        amount: f64,
+        usd_value: Option<f64>,
+    ) -> Result<WithdrawalReservation, FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        let since = withdrawal_window_start(SystemTime::now());
+        conn.transaction::<_, DieselError, _>(|conn| {
+            async move {
+                let limit = withdrawal_limits::table
+                    .filter(withdrawal_limits::wallet_address.eq(wallet_address))
+                    .select(withdrawal_limits::daily_limit_usd)
+                    .for_update()
+                    .first::<f64>(conn)
+                    .await
+                    .optional()?;
+
+                // A withdrawal that cannot be valued may only proceed if the wallet is
+                // unrestricted, in which case there is nothing to record
+                let value = match (limit, usd_value) {
+                    (_, Some(value)) => value,
+                    (None, None) => return Ok(WithdrawalReservation::Unrecorded),
+                    (Some(_), None) => {
+                        let msg = format!("no price for {mint}, cannot enforce withdrawal limit");
+                        return Ok(WithdrawalReservation::Rejected(msg));
+                    },
+                };
+
+                if let Some(limit) = limit {
+                    let withdrawn = withdrawals::table
+                        .filter(withdrawals::wallet_address.eq(wallet_address))
+                        .filter(withdrawals::created_at.gt(since))
+                        .select(sum(withdrawals::usd_value))
+                        .first::<Option<f64>>(conn)
+                        .await?
+                        .unwrap_or_default();
+                    if !within_withdrawal_limit(withdrawn, value, limit) {
+                        let msg = "daily withdrawal limit exceeded".to_string();
+                        return Ok(WithdrawalReservation::Rejected(msg));
+                    }
+                }
+
+                let entry =
+                    Withdrawal::new(wallet_address.to_string(), mint.to_string(), amount, value);
+                let id = entry.id;
+                diesel::insert_into(withdrawals::table).values(entry).execute(conn).await?;
+                Ok(WithdrawalReservation::Reserved(id))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(err_str!(FundsManagerError::Db))
+    }
+
+    /// Release a reserved withdrawal whose transfer failed
+    pub async fn release_withdrawal(&self, id: Uuid) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::delete(withdrawals::table.filter(withdrawals::id.eq(id)))
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
+
+    // ---------------
+    // | Gas Refills |
+    // ---------------
+
+    /// Claim an idempotency key for a gas refill
+    ///
+    /// Records for the key created before `expired_before` are discarded. If
+    /// an unexpired record already exists it is returned and the key is not
+    /// claimed, otherwise the key is claimed and `None` is returned
+    pub async fn claim_gas_refill_key(
+        &self,
+        idempotency_key: &str,
+        amount: f64,
+        expired_before: SystemTime,
+    ) -> Result<Option<GasRefill>, FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::delete(
+            gas_refills::table
+                .filter(gas_refills::idempotency_key.eq(idempotency_key))
+                .filter(gas_refills::created_at.lt(expired_before)),
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(err_str!(FundsManagerError::Db))?;
+
+        let entry = GasRefill::new(idempotency_key.to_string(), amount);
+        let inserted = diesel::insert_into(gas_refills::table)
+            .values(entry)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+        if inserted > 0 {
+            return Ok(None);
+        }
+
+        gas_refills::table
+            .filter(gas_refills::idempotency_key.eq(idempotency_key))
+            .first::<GasRefill>(&mut conn)
+            .await
+            .optional()
+            .map_err(err_str!(FundsManagerError::Db))
+    }
+
+    /// Record the response of a completed gas refill
+    pub async fn complete_gas_refill(
+        &self,
+        idempotency_key: &str,
+        response: &str,
+    ) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::update(gas_refills::table.filter(gas_refills::idempotency_key.eq(idempotency_key)))
+            .set(gas_refills::response.eq(response))
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
+
+    /// Release the idempotency key of a failed gas refill so that it may be
+    /// retried
+    pub async fn release_gas_refill_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::delete(gas_refills::table.filter(gas_refills::idempotency_key.eq(idempotency_key)))
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
+
+    // ---------
+    // | Swaps |
+    // ---------
+
+    /// Get a page of executed swaps, most recent first
+    pub async fn get_swaps(&self, offset: i64, limit: i64) -> Result<Vec<Swap>, FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        swaps::table
+            .order(swaps::created_at.desc())
+            .offset(offset)
+            .limit(limit)
+            .load::<Swap>(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))
+    }
+
+    /// Record an executed swap
+    pub async fn insert_swap(&self, swap: Swap) -> Result<(), FundsManagerError> {
+        let mut conn = self.get_db_conn().await?;
+        diesel::insert_into(swaps::table)
+            .values(swap)
+            .execute(&mut conn)
+            .await
+            .map_err(err_str!(FundsManagerError::Db))?;
+
+        Ok(())
+    }
 }
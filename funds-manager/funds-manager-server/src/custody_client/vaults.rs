@@ -0,0 +1,107 @@
+//! Handlers for moving funds between Fireblocks vaults
+
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use fireblocks_sdk::types::{PeerType, TransactionStatus};
+use funds_manager_api::vaults::{VaultAssetBalance, VaultWithBalances};
+use tracing::{info, warn};
+
+use super::{CustodyClient, DepositWithdrawSource};
+use crate::error::FundsManagerError;
+
+impl CustodyClient {
+    /// Get the balances of all vaults known to the funds manager
+    pub async fn get_vault_balances(&self) -> Result<Vec<VaultWithBalances>, FundsManagerError> {
+        let mut vaults = Vec::new();
+        for source in DepositWithdrawSource::ALL.iter() {
+            let name = source.vault_name();
+            let vault = match self.get_vault_account(name).await? {
+                Some(vault) => vault,
+                None => {
+                    warn!("Vault not found: {name}");
+                    continue;
+                },
+            };
+
+            let balances = vault
+                .assets
+                .into_iter()
+                .map(|asset| VaultAssetBalance {
+                    asset_id: asset.id,
+                    total: asset.total.to_f64().unwrap_or_default(),
+                    available: asset.available.to_f64().unwrap_or_default(),
+                })
+                .collect();
+            vaults.push(VaultWithBalances { name: name.to_string(), balances });
+        }
+
+        Ok(vaults)
+    }
+
+    /// Transfer funds directly from one vault to another
+    pub async fn transfer_between_vaults(
+        &self,
+        source_vault: &str,
+        destination_vault: &str,
+        mint: &str,
+        amount: f64,
+    ) -> Result<(), FundsManagerError> {
+        // Resolve the vault names
+        let source_name = DepositWithdrawSource::from_vault_name(source_vault)?.vault_name();
+        let destination_name =
+            DepositWithdrawSource::from_vault_name(destination_vault)?.vault_name();
+        if source_name == destination_name {
+            return Err(FundsManagerError::custom("Source and destination vaults must differ"));
+        }
+
+        // Look up both vault accounts and the asset to transfer
+        let source = self
+            .get_vault_account(source_name)
+            .await?
+            .ok_or_else(|| FundsManagerError::custom(format!("Vault not found: {source_name}")))?;
+        let destination = self.get_vault_account(destination_name).await?.ok_or_else(|| {
+            FundsManagerError::custom(format!("Vault not found: {destination_name}"))
+        })?;
+        let asset_id = self.get_asset_id_for_address(mint).await?.ok_or_else(|| {
+            FundsManagerError::Custom(format!("Asset not found for mint: {mint}"))
+        })?;
+
+        // Check if the available balance is sufficient
+        let available = source
+            .assets
+            .iter()
+            .find(|a| a.id == asset_id)
+            .map(|acct| acct.available.clone())
+            .unwrap_or_default();
+        let transfer_amount = BigDecimal::from_f64(amount)
+            .ok_or_else(|| FundsManagerError::Custom("Invalid amount".to_string()))?;
+        if available < transfer_amount {
+            return Err(FundsManagerError::Custom(format!(
+                "Insufficient balance. Available: {}, Requested: {}",
+                available, transfer_amount
+            )));
+        }
+
+        // Transfer
+        let client = self.get_fireblocks_client()?;
+        let note = format!("Transfer {amount} {asset_id} from {source_name} to {destination_name}");
+        let (resp, _rid) = client
+            .create_transaction_peer(
+                source.id,
+                &destination.id,
+                PeerType::VAULT_ACCOUNT,
+                asset_id,
+                transfer_amount,
+                Some(&note),
+            )
+            .await?;
+
+        let tx = self.poll_fireblocks_transaction(&resp.id).await?;
+        if tx.status != TransactionStatus::COMPLETED && tx.status != TransactionStatus::CONFIRMING {
+            let err_msg = format!("Transaction failed: {:?}", tx.status);
+            return Err(FundsManagerError::Custom(err_msg));
+        }
+
+        info!("Transferred {amount} {mint} from {source_name} to {destination_name}");
+        Ok(())
+    }
+}
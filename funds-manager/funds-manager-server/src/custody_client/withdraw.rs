@@ -1,14 +1,50 @@
 //! Withdrawal methods for custodied funds
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
 use crate::{error::FundsManagerError, helpers::get_secret};
 use bigdecimal::{BigDecimal, FromPrimitive};
-use ethers::signers::LocalWallet;
+use ethers::{signers::LocalWallet, types::U256, utils::parse_units};
 use fireblocks_sdk::types::{PeerType, TransactionStatus};
 use tracing::info;
+use uuid::Uuid;
 
 use super::{CustodyClient, DepositWithdrawSource};
 
+/// The rolling window over which a hot wallet's withdrawal limit applies
+const WITHDRAWAL_LIMIT_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The outcome of reserving a withdrawal against a hot wallet's daily
+/// withdrawal limit
+///
+/// Limits apply to funds leaving custody into a hot wallet: withdrawals from
+/// the quoter hot wallet and transfers from a vault to its hot wallet.
+/// Transfers from a hot wallet back to its vault and gas withdrawals are not
+/// counted
+pub(crate) enum WithdrawalReservation {
+    /// The withdrawal was recorded under the given id and counts toward the
+    /// limit
+    Reserved(Uuid),
+    /// The wallet has no limit and the withdrawal could not be valued, so
+    /// nothing was recorded
+    Unrecorded,
+    /// The withdrawal was rejected for the given reason
+    Rejected(String),
+}
+
+/// Get the start of the rolling withdrawal limit window ending at `now`
+pub(super) fn withdrawal_window_start(now: SystemTime) -> SystemTime {
+    now - WITHDRAWAL_LIMIT_WINDOW
+}
+
+/// Whether a withdrawal of the given USD value fits within a limit, given the
+/// value already withdrawn in the current window
+pub(super) fn within_withdrawal_limit(withdrawn: f64, usd_value: f64, limit: f64) -> bool {
+    withdrawn + usd_value <= limit
+}
+
 impl CustodyClient {
     /// Withdraw from hot wallet custody with a provided token address
     pub(crate) async fn withdraw_from_hot_wallet(
@@ -17,10 +53,31 @@ impl CustodyClient {
         destination_address: &str,
         token_address: &str,
         amount: f64,
+    ) -> Result<(), FundsManagerError> {
+        let decimals = self.get_erc20_decimals(token_address).await?;
+        let amount_units =
+            parse_units(amount.to_string(), decimals).map_err(FundsManagerError::parse)?.into();
+        self.withdraw_units_from_hot_wallet(
+            source,
+            destination_address,
+            token_address,
+            amount_units,
+        )
+        .await
+    }
+
+    /// Withdraw the given amount, in the token's base units, from hot wallet
+    /// custody with a provided token address
+    pub(crate) async fn withdraw_units_from_hot_wallet(
+        &self,
+        source: DepositWithdrawSource,
+        destination_address: &str,
+        token_address: &str,
+        amount: U256,
     ) -> Result<(), FundsManagerError> {
         // Find the wallet for the given destination and check its balance
         let wallet = self.get_hot_wallet_by_vault(source.vault_name()).await?;
-        let bal = self.get_erc20_balance(token_address, &wallet.address).await?;
+        let (bal, _) = self.get_erc20_balance_units(token_address, &wallet.address).await?;
         if bal < amount {
             return Err(FundsManagerError::Custom("Insufficient balance".to_string()));
         }
@@ -29,7 +86,8 @@ impl CustodyClient {
         let wallet = self.get_hot_wallet_private_key(&wallet.address).await?;
 
         // Execute the erc20 transfer
-        let tx = self.erc20_transfer(token_address, destination_address, amount, wallet).await?;
+        let tx =
+            self.erc20_transfer_units(token_address, destination_address, amount, wallet).await?;
         info!(
             "Withdrew {amount} {token_address} from hot wallet to {destination_address}. Tx: {:?}",
             tx.transaction_hash
@@ -103,11 +161,22 @@ impl CustodyClient {
         &self,
         amount: f64,
         to: &str,
+    ) -> Result<(), FundsManagerError> {
+        let amount_wei =
+            parse_units(amount.to_string(), "ether").map_err(FundsManagerError::parse)?.into();
+        self.withdraw_gas_wei(amount_wei, to).await
+    }
+
+    /// Withdraw the given amount of gas in wei
+    pub(crate) async fn withdraw_gas_wei(
+        &self,
+        amount: U256,
+        to: &str,
     ) -> Result<(), FundsManagerError> {
         // Check the gas wallet's balance
         let gas_vault_name = DepositWithdrawSource::Gas.vault_name();
         let gas_wallet = self.get_hot_wallet_by_vault(gas_vault_name).await?;
-        let bal = self.get_ether_balance(&gas_wallet.address).await?;
+        let bal = self.get_ether_balance_wei(&gas_wallet.address).await?;
         if bal < amount {
             return Err(FundsManagerError::custom("Insufficient balance"));
         }
@@ -119,9 +188,44 @@ impl CustodyClient {
             LocalWallet::from_str(private_key.as_str()).map_err(FundsManagerError::parse)?;
 
         // Execute the transfer
-        let tx = self.transfer_ether(to, amount, wallet).await?;
-        info!("Withdrew {amount} ETH from gas wallet to {to}. Tx: {:#}", tx.transaction_hash);
+        let tx = self.transfer_ether_wei(to, amount, wallet).await?;
+        info!("Withdrew {amount} wei from gas wallet to {to}. Tx: {:#}", tx.transaction_hash);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{withdrawal_window_start, within_withdrawal_limit, WITHDRAWAL_LIMIT_WINDOW};
+
+    /// Tests that the limit window spans the 24 hours preceding now
+    #[test]
+    fn test_withdrawal_window_start() {
+        let now = SystemTime::now();
+        let start = withdrawal_window_start(now);
+        assert_eq!(now.duration_since(start).unwrap(), WITHDRAWAL_LIMIT_WINDOW);
+
+        // A withdrawal an hour inside the window counts, one an hour outside does not
+        let hour = Duration::from_secs(60 * 60);
+        assert!(now - (WITHDRAWAL_LIMIT_WINDOW - hour) > start);
+        assert!(now - (WITHDRAWAL_LIMIT_WINDOW + hour) < start);
+    }
+
+    /// Tests the limit check against prior withdrawals in the window
+    #[test]
+    fn test_within_withdrawal_limit() {
+        // A withdrawal exactly reaching the limit is allowed
+        assert!(within_withdrawal_limit(0., 100., 100.));
+        assert!(within_withdrawal_limit(60., 40., 100.));
+
+        // Prior withdrawals in the window count toward the limit
+        assert!(!within_withdrawal_limit(60., 40.01, 100.));
+        assert!(!within_withdrawal_limit(100., 1., 100.));
+
+        // A zero limit blocks any withdrawal with value
+        assert!(!within_withdrawal_limit(0., 0.01, 0.));
+    }
+}
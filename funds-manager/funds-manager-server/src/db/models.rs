@@ -52,7 +52,7 @@ impl NewFee {
 }
 
 /// Metadata information maintained by the indexer
-#[derive(Clone, Queryable, Selectable)]
+#[derive(Clone, Queryable, Selectable, Insertable)]
 #[diesel(table_name = crate::db::schema::indexing_metadata)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[allow(missing_docs, clippy::missing_docs_in_private_items)]
@@ -173,3 +173,71 @@ impl GasWallet {
         GasWallet { id, address, peer_id: None, status, created_at: SystemTime::now() }
     }
 }
+
+/// A gas refill request, keyed by its idempotency key
+///
+/// The response is populated once the refill completes
+#[derive(Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::db::schema::gas_refills)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct GasRefill {
+    pub idempotency_key: String,
+    pub amount: f64,
+    pub response: Option<String>,
+    pub created_at: SystemTime,
+}
+
+impl GasRefill {
+    /// Construct a new, in-progress gas refill record
+    pub fn new(idempotency_key: String, amount: f64) -> Self {
+        GasRefill { idempotency_key, amount, response: None, created_at: SystemTime::now() }
+    }
+}
+
+/// The daily withdrawal limit of a hot wallet
+#[derive(Clone, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = crate::db::schema::withdrawal_limits)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WithdrawalLimit {
+    pub wallet_address: String,
+    pub daily_limit_usd: f64,
+}
+
+/// A withdrawal from a hot wallet, recorded for rolling limit accounting
+#[derive(Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::db::schema::withdrawals)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Withdrawal {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub mint: String,
+    pub amount: f64,
+    pub usd_value: f64,
+    pub created_at: SystemTime,
+}
+
+impl Withdrawal {
+    /// Construct a new withdrawal record
+    pub fn new(wallet_address: String, mint: String, amount: f64, usd_value: f64) -> Self {
+        let id = Uuid::new_v4();
+        Withdrawal { id, wallet_address, mint, amount, usd_value, created_at: SystemTime::now() }
+    }
+}
+
+/// A swap executed on the quoter hot wallet
+#[derive(Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::db::schema::swaps)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Swap {
+    pub id: Uuid,
+    pub venue: String,
+    pub source: String,
+    pub sell_token: String,
+    pub buy_token: String,
+    pub sell_amount: BigDecimal,
+    pub quoted_price: BigDecimal,
+    pub buy_amount_actual: BigDecimal,
+    pub gas_cost: BigDecimal,
+    pub tx_hash: String,
+    pub created_at: SystemTime,
+}
@@ -12,6 +12,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    gas_refills (idempotency_key) {
+        idempotency_key -> Text,
+        amount -> Float8,
+        response -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     gas_wallets (id) {
         id -> Uuid,
@@ -47,10 +56,48 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    swaps (id) {
+        id -> Uuid,
+        venue -> Text,
+        source -> Text,
+        sell_token -> Text,
+        buy_token -> Text,
+        sell_amount -> Numeric,
+        quoted_price -> Numeric,
+        buy_amount_actual -> Numeric,
+        gas_cost -> Numeric,
+        tx_hash -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    withdrawal_limits (wallet_address) {
+        wallet_address -> Text,
+        daily_limit_usd -> Float8,
+    }
+}
+
+diesel::table! {
+    withdrawals (id) {
+        id -> Uuid,
+        wallet_address -> Text,
+        mint -> Text,
+        amount -> Float8,
+        usd_value -> Float8,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::allow_tables_to_appear_in_same_query!(
     fees,
+    gas_refills,
     gas_wallets,
     hot_wallets,
     indexing_metadata,
     renegade_wallets,
+    swaps,
+    withdrawal_limits,
+    withdrawals,
 );
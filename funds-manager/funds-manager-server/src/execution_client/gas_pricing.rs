@@ -0,0 +1,118 @@
+//! Gas pricing strategies for swap transactions
+
+use clap::ValueEnum;
+use ethers::{
+    providers::Middleware,
+    types::{
+        transaction::eip2718::TypedTransaction, BlockNumber, Eip1559TransactionRequest,
+        TransactionRequest, U256,
+    },
+    utils::parse_units,
+};
+
+use super::{error::ExecutionClientError, ExecutionClient};
+
+/// The transaction type used to price gas, selected via the CLI
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GasPricingMode {
+    /// Send EIP-1559 transactions
+    Eip1559,
+    /// Send legacy transactions with a single gas price
+    Legacy,
+}
+
+/// The strategy used to price gas for swap transactions
+#[derive(Clone, Copy, Debug)]
+pub enum GasPricingStrategy {
+    /// Price an EIP-1559 transaction
+    ///
+    /// If a multiplier is given, the max fee is set to the latest base fee
+    /// scaled by the multiplier; otherwise the fees are estimated by the
+    /// provider
+    Eip1559 {
+        /// The multiple of the latest base fee to use as the max fee
+        multiplier: Option<f64>,
+    },
+    /// Price a legacy transaction
+    ///
+    /// If a gas price is given it is used as-is; otherwise the gas price is
+    /// estimated by the provider
+    Legacy {
+        /// The gas price in wei
+        gas_price: Option<U256>,
+    },
+}
+
+impl GasPricingStrategy {
+    /// Build a gas pricing strategy from its CLI configuration
+    pub fn new(
+        mode: GasPricingMode,
+        base_fee_multiplier: Option<f64>,
+        legacy_gas_price_gwei: Option<f64>,
+    ) -> Result<Self, ExecutionClientError> {
+        match mode {
+            GasPricingMode::Eip1559 => {
+                if let Some(multiplier) = base_fee_multiplier {
+                    if !multiplier.is_finite() || multiplier < 1. {
+                        return Err(ExecutionClientError::parse(
+                            "base fee multiplier must be at least 1",
+                        ));
+                    }
+                }
+
+                Ok(Self::Eip1559 { multiplier: base_fee_multiplier })
+            },
+            GasPricingMode::Legacy => {
+                let gas_price = legacy_gas_price_gwei
+                    .map(|gwei| parse_units(gwei.to_string(), "gwei"))
+                    .transpose()
+                    .map_err(ExecutionClientError::parse)?
+                    .map(U256::from);
+
+                Ok(Self::Legacy { gas_price })
+            },
+        }
+    }
+}
+
+impl ExecutionClient {
+    /// Apply the configured gas pricing strategy to a transaction
+    pub(crate) async fn price_transaction(
+        &self,
+        tx: Eip1559TransactionRequest,
+    ) -> Result<TypedTransaction, ExecutionClientError> {
+        match self.gas_pricing {
+            GasPricingStrategy::Eip1559 { multiplier: None } => Ok(tx.into()),
+            GasPricingStrategy::Eip1559 { multiplier: Some(multiplier) } => {
+                let base_fee = self.get_base_fee().await?;
+                let max_fee = scale_u256(base_fee, multiplier);
+                Ok(tx.max_fee_per_gas(max_fee).into())
+            },
+            GasPricingStrategy::Legacy { gas_price } => {
+                let mut legacy = TransactionRequest::new();
+                legacy.from = tx.from;
+                legacy.to = tx.to;
+                legacy.value = tx.value;
+                legacy.data = tx.data;
+                legacy.gas_price = gas_price;
+                Ok(legacy.into())
+            },
+        }
+    }
+
+    /// Get the base fee of the latest block
+    pub(crate) async fn get_base_fee(&self) -> Result<U256, ExecutionClientError> {
+        self.rpc_provider
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(ExecutionClientError::arbitrum)?
+            .and_then(|block| block.base_fee_per_gas)
+            .ok_or_else(|| ExecutionClientError::arbitrum("No base fee in latest block"))
+    }
+}
+
+/// Scale a `U256` by a floating point multiplier, with basis point precision
+fn scale_u256(value: U256, multiplier: f64) -> U256 {
+    let bps = U256::from((multiplier * 10_000.).round() as u64);
+    value.saturating_mul(bps) / U256::from(10_000u64)
+}
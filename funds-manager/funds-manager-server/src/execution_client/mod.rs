@@ -1,6 +1,7 @@
 //! Client for interacting with execution venues, currently this is the 0x swap
 //! API
 pub mod error;
+pub mod gas_pricing;
 pub mod quotes;
 pub mod swap;
 
@@ -17,9 +18,14 @@ use serde::Deserialize;
 use tracing::error;
 
 use self::error::ExecutionClientError;
+use self::gas_pricing::GasPricingStrategy;
 
 /// The 0x api key header
 const API_KEY_HEADER: &str = "0x-api-key";
+/// The name of the execution venue, recorded alongside executed swaps
+pub const EXECUTION_VENUE_NAME: &str = "0x";
+/// The default number of confirmations to await for swap transactions
+pub const DEFAULT_SWAP_CONFIRMATIONS: usize = 1;
 
 /// The client for interacting with the execution venue
 #[derive(Clone)]
@@ -32,6 +38,13 @@ pub struct ExecutionClient {
     http_client: Arc<Client>,
     /// The RPC provider
     rpc_provider: Arc<Provider<Http>>,
+    /// The number of confirmations to await for swap transactions
+    confirmations: usize,
+    /// Whether to skip sending swaps on-chain, returning synthetic results
+    /// instead
+    dry_run: bool,
+    /// The strategy used to price gas for swap transactions
+    gas_pricing: GasPricingStrategy,
 }
 
 impl ExecutionClient {
@@ -40,6 +53,9 @@ impl ExecutionClient {
         api_key: String,
         base_url: String,
         rpc_url: &str,
+        confirmations: usize,
+        dry_run: bool,
+        gas_pricing: GasPricingStrategy,
     ) -> Result<Self, ExecutionClientError> {
         let provider =
             Provider::<Http>::try_from(rpc_url).map_err(ExecutionClientError::arbitrum)?;
@@ -48,9 +64,17 @@ impl ExecutionClient {
             base_url,
             http_client: Arc::new(Client::new()),
             rpc_provider: Arc::new(provider),
+            confirmations,
+            dry_run,
+            gas_pricing,
         })
     }
 
+    /// Whether the client is in dry-run mode
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     /// Get a full URL for a given endpoint
     fn build_url(
         &self,
@@ -112,6 +112,7 @@ impl ExecutionClient {
         let pending_tx = tx.send().await.map_err(ExecutionClientError::arbitrum)?;
 
         let receipt = pending_tx
+            .confirmations(self.confirmations)
             .await
             .map_err(ExecutionClientError::arbitrum)?
             .ok_or_else(|| ExecutionClientError::arbitrum("Transaction failed"))?;
@@ -3,41 +3,104 @@
 use ethers::{
     providers::Middleware,
     signers::LocalWallet,
-    types::{Eip1559TransactionRequest, TransactionReceipt},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest,
+        TransactionReceipt, H256, U256,
+    },
+    utils::keccak256,
 };
 use funds_manager_api::quoters::ExecutionQuote;
 use tracing::info;
 
-use crate::helpers::TransactionHash;
+use crate::helpers::dry_run_receipt;
 
 use super::{error::ExecutionClientError, ExecutionClient};
 
+/// The signature of the ERC20 `Transfer` event
+const TRANSFER_EVENT_SIGNATURE: &str = "Transfer(address,address,uint256)";
+
 impl ExecutionClient {
     /// Execute a quoted swap
     pub async fn execute_swap(
         &self,
         quote: ExecutionQuote,
         wallet: &LocalWallet,
-    ) -> Result<TransactionHash, ExecutionClientError> {
+    ) -> Result<TransactionReceipt, ExecutionClientError> {
         // Execute the swap
         let receipt = self.execute_swap_tx(quote, wallet).await?;
-        let tx_hash = receipt.transaction_hash;
-        info!("Swap executed at {tx_hash:#x}");
-        Ok(tx_hash)
+        info!("Swap executed at {:#x}", receipt.transaction_hash);
+        Ok(receipt)
     }
 
-    /// Execute a swap
-    async fn execute_swap_tx(
+    /// Get the amount of a token transferred to the recipient in a swap
+    pub fn get_amount_received(
+        receipt: &TransactionReceipt,
+        token: Address,
+        recipient: Address,
+    ) -> U256 {
+        let transfer_topic = H256::from(keccak256(TRANSFER_EVENT_SIGNATURE));
+        let recipient_topic = H256::from(recipient);
+        receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == token)
+            .filter(|log| log.topics.first() == Some(&transfer_topic))
+            .filter(|log| log.topics.get(2) == Some(&recipient_topic))
+            .map(|log| U256::from_big_endian(&log.data))
+            .fold(U256::zero(), |acc, amount| acc.saturating_add(amount))
+    }
+
+    /// Get the gas cost of a transaction in wei
+    pub fn get_gas_cost(receipt: &TransactionReceipt) -> U256 {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        let gas_price = receipt.effective_gas_price.unwrap_or_default();
+        gas_used.saturating_mul(gas_price)
+    }
+
+    /// Estimate the gas used by a quoted swap and the current base fee
+    ///
+    /// Returns `(gas, base_fee_per_gas)`. The swap is not sent
+    pub async fn estimate_swap_gas(
         &self,
         quote: ExecutionQuote,
-        wallet: &LocalWallet,
-    ) -> Result<TransactionReceipt, ExecutionClientError> {
-        let client = self.get_signer(wallet.clone());
+    ) -> Result<(U256, U256), ExecutionClientError> {
+        let tx = self.build_swap_tx(quote).await?;
+        let gas = self
+            .rpc_provider
+            .estimate_gas(&tx, None /* block */)
+            .await
+            .map_err(ExecutionClientError::arbitrum)?;
+
+        let base_fee = self.get_base_fee().await?;
+        Ok((gas, base_fee))
+    }
+
+    /// Build the transaction for a quoted swap, priced according to the
+    /// configured gas pricing strategy
+    async fn build_swap_tx(
+        &self,
+        quote: ExecutionQuote,
+    ) -> Result<TypedTransaction, ExecutionClientError> {
         let tx = Eip1559TransactionRequest::new()
             .to(quote.to)
             .from(quote.from)
             .value(quote.value)
             .data(quote.data);
+        self.price_transaction(tx).await
+    }
+
+    /// Execute a swap
+    async fn execute_swap_tx(
+        &self,
+        quote: ExecutionQuote,
+        wallet: &LocalWallet,
+    ) -> Result<TransactionReceipt, ExecutionClientError> {
+        let client = self.get_signer(wallet.clone());
+        let tx = self.build_swap_tx(quote).await?;
+        if self.dry_run {
+            info!("Dry run: skipping swap submission");
+            return Ok(dry_run_receipt());
+        }
 
         // Send the transaction
         let pending_tx = client
@@ -45,6 +108,7 @@ impl ExecutionClient {
             .await
             .map_err(ExecutionClientError::arbitrum)?;
         pending_tx
+            .confirmations(self.confirmations)
             .await
             .map_err(ExecutionClientError::arbitrum)?
             .ok_or_else(|| ExecutionClientError::arbitrum("Transaction failed"))
@@ -1,10 +1,12 @@
 //! Phase one of the sweeper's execution; index all fees since the last
 //! consistent block
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use alloy_sol_types::SolCall;
-use ethers::contract::LogMeta;
 use ethers::middleware::Middleware;
 use ethers::types::TxHash;
+use futures::stream::{self, StreamExt};
 use renegade_arbitrum_client::abi::settleOfflineFeeCall;
 use renegade_arbitrum_client::{
     abi::NotePostedFilter, constants::SELECTOR_LEN,
@@ -17,16 +19,41 @@ use renegade_circuit_types::wallet::NoteCommitment;
 use renegade_constants::Scalar;
 use renegade_crypto::fields::{scalar_to_biguint, scalar_to_u128, u256_to_scalar};
 use renegade_util::err_str;
-use tracing::{info, warn};
+use renegade_util::hex::jubjub_to_hex_string;
+use tracing::{error, info, warn};
 
 use crate::db::models::NewFee;
 use crate::error::FundsManagerError;
 use crate::Indexer;
 
+/// The maximum number of decryption keys to index concurrently
+const MAX_CONCURRENT_KEY_INDEXING: usize = 4;
+/// The maximum number of transactions fetched concurrently while indexing
+const MAX_CONCURRENT_TX_FETCHES: usize = 8;
+
+/// The notes posted in each block, as (note commitment, tx hash) pairs
+type BlockNotes = BTreeMap<u64, Vec<(NoteCommitment, TxHash)>>;
+/// The note ciphertext of each transaction, or the error encountered while
+/// fetching it
+type NoteCiphertexts = HashMap<TxHash, Result<ElGamalCiphertext<NOTE_CIPHERTEXT_SIZE>, String>>;
+
 impl Indexer {
-    /// Index all fees since the given block
+    /// Index all fees since the last indexed block
+    ///
+    /// Each decryption key addresses a disjoint set of fees, so the keys are
+    /// indexed concurrently, each from its own latest indexed block. A key
+    /// commits its fees and advances its checkpoint one block at a time, so a
+    /// failure on one key neither aborts nor holds back the others
     pub async fn index_fees(&self) -> Result<(), FundsManagerError> {
-        let block_number = self.get_latest_block().await?;
+        if self.decryption_keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut checkpoints = Vec::with_capacity(self.decryption_keys.len());
+        for key in self.decryption_keys.iter() {
+            checkpoints.push(self.get_key_latest_block(key).await?);
+        }
+        let block_number = checkpoints.iter().copied().min().unwrap_or_default();
         info!("indexing fees from block {block_number}");
 
         let darkpool_addr = self.arbitrum_client.get_darkpool_client().address();
@@ -42,34 +69,121 @@ impl Indexer {
             .await
             .map_err(|_| FundsManagerError::arbitrum("failed to create note posted stream"))?;
 
-        let mut most_recent_block = block_number;
-        for (event, meta) in events {
-            let block = meta.block_number.as_u64();
+        let mut blocks = BlockNotes::new();
+        for (event, meta) in events.iter() {
             let note_comm = u256_to_scalar(&event.note_commitment);
-            self.index_note(note_comm, meta).await?;
+            let block = blocks.entry(meta.block_number.as_u64()).or_default();
+            block.push((note_comm, meta.transaction_hash));
+        }
+
+        // Fetch each transaction's ciphertext once, shared between the keys
+        let ciphertexts = self.get_ciphertexts(&blocks).await;
+
+        // Index the notes for each key concurrently
+        let results: Vec<_> = stream::iter(self.decryption_keys.iter().zip(checkpoints))
+            .map(|(key, checkpoint)| {
+                let blocks = &blocks;
+                let ciphertexts = &ciphertexts;
+                async move {
+                    let res = self.index_fees_for_key(key, checkpoint, blocks, ciphertexts).await;
+                    (key, res)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_KEY_INDEXING)
+            .collect()
+            .await;
 
-            if block > most_recent_block {
-                most_recent_block = block;
-                self.update_latest_block(most_recent_block).await?;
+        let n_keys = results.len();
+        let failures: Vec<String> = results
+            .into_iter()
+            .filter_map(|(key, res)| {
+                let receiver = jubjub_to_hex_string(&key.public_key());
+                res.err().map(|e| {
+                    error!("failed to index fees for receiver {receiver}: {e}");
+                    format!("{receiver}: {e}")
+                })
+            })
+            .collect();
+
+        // The global checkpoint tracks the block indexed for all keys
+        let mut min_checkpoint = u64::MAX;
+        for key in self.decryption_keys.iter() {
+            min_checkpoint = min_checkpoint.min(self.get_key_latest_block(key).await?);
+        }
+        self.update_latest_block(min_checkpoint).await?;
+
+        if !failures.is_empty() {
+            let n_succeeded = n_keys - failures.len();
+            return Err(FundsManagerError::custom(format!(
+                "indexed fees for {n_succeeded}/{n_keys} decryption keys, failed: {}",
+                failures.join("; ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the note ciphertexts of all transactions in the given blocks
+    async fn get_ciphertexts(&self, blocks: &BlockNotes) -> NoteCiphertexts {
+        let tx_hashes: HashSet<TxHash> =
+            blocks.values().flatten().map(|(_, tx_hash)| *tx_hash).collect();
+
+        stream::iter(tx_hashes)
+            .map(|tx_hash| async move {
+                let res = self.get_ciphertext_from_tx(tx_hash).await.map_err(|e| e.to_string());
+                (tx_hash, res)
+            })
+            .buffer_unordered(MAX_CONCURRENT_TX_FETCHES)
+            .collect()
+            .await
+    }
+
+    /// Index all notes addressed to the given key in blocks at or after the
+    /// key's checkpoint
+    ///
+    /// The fees found in each block are inserted in the same transaction that
+    /// advances the key's checkpoint to that block
+    async fn index_fees_for_key(
+        &self,
+        key: &DecryptionKey,
+        checkpoint: u64,
+        blocks: &BlockNotes,
+        ciphertexts: &NoteCiphertexts,
+    ) -> Result<(), FundsManagerError> {
+        for (block, notes) in blocks.range(checkpoint..) {
+            let mut fees = Vec::new();
+            for (note_comm, tx_hash) in notes {
+                let cipher = match ciphertexts.get(tx_hash) {
+                    Some(Ok(cipher)) => cipher,
+                    Some(Err(e)) => return Err(FundsManagerError::arbitrum(e.clone())),
+                    None => return Err(FundsManagerError::arbitrum("tx not fetched")),
+                };
+
+                if let Some(fee) = self.get_fee_for_note(key, cipher, *note_comm, *tx_hash).await? {
+                    fees.push(fee);
+                }
             }
+
+            self.insert_fees_for_key(key, fees, *block).await?;
         }
 
         Ok(())
     }
 
-    /// Index a note
-    async fn index_note(
+    /// Get the fee for a note if it is addressed to the given key and unspent
+    async fn get_fee_for_note(
         &self,
+        key: &DecryptionKey,
+        cipher: &ElGamalCiphertext<NOTE_CIPHERTEXT_SIZE>,
         note_comm: NoteCommitment,
-        meta: LogMeta,
-    ) -> Result<(), FundsManagerError> {
-        let maybe_note = self.get_note_from_tx(meta.transaction_hash, note_comm).await?;
-        let tx = format!("{:#x}", meta.transaction_hash);
-        let note = match maybe_note {
+        tx_hash: TxHash,
+    ) -> Result<Option<NewFee>, FundsManagerError> {
+        let tx = format!("{tx_hash:#x}");
+        let note = match self.decrypt_note(cipher, key, note_comm) {
             Some(note) => note,
             None => {
                 info!("not the note receiver, skipping...");
-                return Ok(());
+                return Ok(None);
             },
         };
         info!("indexing note from tx: {tx}");
@@ -83,26 +197,11 @@ impl Indexer {
             .map_err(|_| FundsManagerError::db("failed to check nullifier"))?
         {
             info!("note nullifier already spent, skipping");
-            return Ok(());
+            return Ok(None);
         }
 
         // Otherwise, index the note
-        let fee = NewFee::new_from_note(&note, tx);
-        self.insert_fee(fee).await
-    }
-
-    /// Get a note from a transaction body
-    ///
-    /// Checks the note's commitment against the provided commitment, returning
-    /// `None` if they do not match
-    pub(crate) async fn get_note_from_tx(
-        &self,
-        tx_hash: TxHash,
-        note_comm: NoteCommitment,
-    ) -> Result<Option<Note>, FundsManagerError> {
-        // Parse the note from the tx then decrypt it
-        let cipher = self.get_ciphertext_from_tx(tx_hash).await?;
-        Ok(self.decrypt_note(&cipher, note_comm))
+        Ok(Some(NewFee::new_from_note(&note, tx)))
     }
 
     /// Get a note from a transaction body using the given key to decrypt it
@@ -150,10 +249,11 @@ impl Indexer {
     /// Decrypt a note using the decryption key
     ///
     /// Checks the decryption against the note's expected commitment, returns
-    /// `None` if the note does not match for any of the provided key
+    /// `None` if the note does not match for the provided key
     fn decrypt_note(
         &self,
         note: &ElGamalCiphertext<NOTE_CIPHERTEXT_SIZE>,
+        key: &DecryptionKey,
         note_comm: NoteCommitment,
     ) -> Option<Note> {
         if !note.is_valid_ciphertext() {
@@ -162,14 +262,8 @@ impl Indexer {
         }
 
         // The ciphertext stores all note values except the encryption key
-        for key in self.decryption_keys.iter() {
-            let note = self.decrypt_note_with_key(note, key);
-            if note.commitment() == note_comm {
-                return Some(note);
-            }
-        }
-
-        None
+        let note = self.decrypt_note_with_key(note, key);
+        (note.commitment() == note_comm).then_some(note)
     }
 
     /// Decrypt a note using the given key
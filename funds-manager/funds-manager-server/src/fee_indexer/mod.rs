@@ -35,6 +35,8 @@ pub(crate) struct Indexer {
     pub aws_config: AwsConfig,
     /// The custody client
     pub custody_client: CustodyClient,
+    /// The minimum USD value of a fee for it to be redeemed
+    pub min_redemption_value_usd: f64,
 }
 
 impl Indexer {
@@ -49,6 +51,7 @@ impl Indexer {
         db_pool: Arc<DbPool>,
         relayer_client: RelayerClient,
         custody_client: CustodyClient,
+        min_redemption_value_usd: f64,
     ) -> Self {
         Indexer {
             chain_id,
@@ -59,6 +62,7 @@ impl Indexer {
             relayer_client,
             aws_config,
             custody_client,
+            min_redemption_value_usd,
         }
     }
 
@@ -11,12 +11,14 @@ use diesel::sql_query;
 use diesel::sql_types::SingleValue;
 use diesel::sql_types::{Array, Integer, Nullable, Numeric, Text};
 use diesel::ExpressionMethods;
+use diesel::OptionalExtension;
 use diesel::PgArrayExpressionMethods;
 use diesel::QueryDsl;
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use renegade_circuit_types::elgamal::DecryptionKey;
 use renegade_common::types::wallet::WalletIdentifier;
 use renegade_constants::MAX_BALANCES;
-use tracing::warn;
+use renegade_util::hex::jubjub_to_hex_string;
 use uuid::Uuid;
 
 use crate::db::models::RenegadeWalletMetadata;
@@ -40,6 +42,12 @@ use super::redeem_fees::MAX_FEES_REDEEMED;
 /// The metadata key for the last indexed block
 pub(crate) const LAST_INDEXED_BLOCK_KEY: &str = "latest_block";
 
+/// Get the metadata key for the last block indexed for a decryption key
+fn key_latest_block_key(key: &DecryptionKey) -> String {
+    let receiver = jubjub_to_hex_string(&key.public_key());
+    format!("{LAST_INDEXED_BLOCK_KEY}_{receiver}")
+}
+
 // Define the `array_length` function
 sql_function! {
     /// Calculate the length of an array
@@ -74,7 +82,6 @@ pub(crate) struct FeeValue {
     pub receiver: String,
     /// The value of the fee
     #[sql_type = "Numeric"]
-    #[allow(unused)]
     pub value: BigDecimal,
 }
 
@@ -104,6 +111,30 @@ impl Indexer {
             .map_err(|_| FundsManagerError::db("could not parse latest block"))
     }
 
+    /// Get the latest block number indexed for the given decryption key
+    ///
+    /// Falls back to the global latest block if the key has not been indexed
+    pub(crate) async fn get_key_latest_block(
+        &self,
+        key: &DecryptionKey,
+    ) -> Result<u64, FundsManagerError> {
+        let mut conn = self.get_conn().await?;
+        let entry = metadata_table
+            .filter(metadata_key.eq(key_latest_block_key(key)))
+            .first::<Metadata>(&mut conn)
+            .await
+            .optional()
+            .map_err(|_| FundsManagerError::db("failed to query latest block"))?;
+
+        match entry {
+            Some(entry) => entry
+                .value
+                .parse::<u64>()
+                .map_err(|_| FundsManagerError::db("could not parse latest block")),
+            None => self.get_latest_block().await,
+        }
+    }
+
     /// Update the latest block number
     pub(crate) async fn update_latest_block(
         &self,
@@ -123,20 +154,43 @@ impl Indexer {
     // | Fees Table |
     // --------------
 
-    /// Insert a fee into the fees table
-    pub(crate) async fn insert_fee(&self, fee: NewFee) -> Result<(), FundsManagerError> {
+    /// Insert the fees found in a block for the given decryption key, and
+    /// advance the key's latest indexed block to that block
+    ///
+    /// Both writes happen in a single transaction, so a key's checkpoint never
+    /// advances past fees that were not inserted. Fees that already exist are
+    /// skipped
+    pub(crate) async fn insert_fees_for_key(
+        &self,
+        key: &DecryptionKey,
+        fees: Vec<NewFee>,
+        block_number: u64,
+    ) -> Result<(), FundsManagerError> {
         let mut conn = self.get_conn().await?;
-        match diesel::insert_into(fees_table).values(vec![fee]).execute(&mut conn).await {
-            Ok(_) => Ok(()),
-            Err(DieselError::DatabaseError(
-                diesel::result::DatabaseErrorKind::UniqueViolation,
-                _,
-            )) => {
-                warn!("Fee already exists in the database, skipping insertion...",);
+        let entry = Metadata { key: key_latest_block_key(key), value: block_number.to_string() };
+        conn.transaction::<_, DieselError, _>(|conn| {
+            async move {
+                if !fees.is_empty() {
+                    diesel::insert_into(fees_table)
+                        .values(fees)
+                        .on_conflict_do_nothing()
+                        .execute(conn)
+                        .await?;
+                }
+
+                diesel::insert_into(metadata_table)
+                    .values(&entry)
+                    .on_conflict(metadata_key)
+                    .do_update()
+                    .set(metadata_value.eq(&entry.value))
+                    .execute(conn)
+                    .await?;
                 Ok(())
-            },
-            Err(e) => Err(FundsManagerError::db(format!("failed to insert fee: {e}"))),
-        }
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| FundsManagerError::db(format!("failed to insert fees: {e}")))
     }
 
     /// Get all mints that have unredeemed fees
@@ -173,7 +227,7 @@ impl Indexer {
     /// Returns the `MAX_FEES_REDEEMED` most valuable fees to be redeemed
     pub(crate) async fn get_most_valuable_fees(
         &self,
-        prices: HashMap<String, f64>,
+        prices: &HashMap<String, f64>,
     ) -> Result<Vec<FeeValue>, FundsManagerError> {
         if prices.is_empty() {
             return Ok(vec![]);
@@ -195,7 +249,7 @@ impl Indexer {
         query_string.push_str("CASE ");
 
         // Add the cases
-        for (mint, price) in prices.into_iter() {
+        for (mint, price) in prices.iter() {
             query_string.push_str(&format!("WHEN mint = '{}' then amount * {} ", mint, price));
         }
         query_string.push_str("ELSE 0 END as value ");
@@ -4,12 +4,14 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use aws_sdk_secretsmanager::Client as SecretsManagerClient;
+use bigdecimal::ToPrimitive;
 use ethers::core::rand::thread_rng;
 use ethers::signers::LocalWallet;
 use ethers::types::TxHash;
 use ethers::utils::hex;
 use renegade_api::http::wallet::RedeemNoteRequest;
 use renegade_circuit_types::note::Note;
+use renegade_common::types::token::Token;
 use renegade_common::types::wallet::derivation::{
     derive_blinder_seed, derive_share_seed, derive_wallet_id, derive_wallet_keychain,
 };
@@ -22,6 +24,8 @@ use crate::error::FundsManagerError;
 use crate::helpers::create_secrets_manager_entry_with_description;
 use crate::Indexer;
 
+use super::queries::FeeValue;
+
 /// The maximum number of fees to redeem in a given run of the indexer
 pub(crate) const MAX_FEES_REDEEMED: usize = 20;
 
@@ -46,18 +50,47 @@ impl Indexer {
         }
 
         // Get the most valuable fees and redeem them
-        let most_valuable_fees = self.get_most_valuable_fees(prices).await?;
+        let most_valuable_fees = self.get_most_valuable_fees(&prices).await?;
 
-        // TODO: Filter by those fees whose present value exceeds the expected gas costs
-        // to redeem
+        let mut skipped_value = 0.;
         for fee in most_valuable_fees.into_iter() {
+            if let Some(usd_value) = self.get_fee_usd_value(&fee, &prices) {
+                if usd_value < self.min_redemption_value_usd {
+                    info!(
+                        "skipping redemption of fee in {} ({}) worth ${usd_value:.2}, below threshold",
+                        fee.tx_hash, fee.mint
+                    );
+                    skipped_value += usd_value;
+                    continue;
+                }
+            }
+
             let wallet = self.get_or_create_wallet(&fee.mint).await?;
             self.redeem_note_into_wallet(fee.tx_hash.clone(), fee.receiver, wallet).await?;
         }
 
+        if skipped_value > 0. {
+            info!("${skipped_value:.2} of fees pending redemption below threshold");
+        }
+
         Ok(())
     }
 
+    /// Get the USD value of a fee
+    ///
+    /// Returns `None` if the fee's mint has no price or its token decimals are
+    /// unknown, in which case the redemption threshold is not applied
+    fn get_fee_usd_value(&self, fee: &FeeValue, prices: &HashMap<String, f64>) -> Option<f64> {
+        // Fees in mints without a price are valued at zero by the query
+        if !prices.contains_key(&fee.mint) {
+            return None;
+        }
+
+        let decimals = Token::from_addr(&fee.mint).get_decimals()?;
+        let value = fee.value.to_f64()?;
+        Some(value / 10f64.powi(decimals as i32))
+    }
+
     // -------------------
     // | Wallet Creation |
     // -------------------
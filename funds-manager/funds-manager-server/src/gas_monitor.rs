@@ -0,0 +1,144 @@
+//! A background task that alerts when active gas wallets run low on ETH
+
+use std::{sync::Arc, time::Duration};
+
+use reqwest::Client;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::{custody_client::CustodyClient, error::FundsManagerError};
+
+// -------------
+// | Constants |
+// -------------
+
+/// The metric recording the ETH balance of a gas wallet
+const GAS_WALLET_BALANCE_METRIC: &str = "gas_wallet_balance";
+/// The metric counting low balance alerts raised for gas wallets
+const GAS_WALLET_LOW_BALANCE_METRIC: &str = "gas_wallet_low_balance";
+/// The metric tag for a gas wallet's address
+const WALLET_ADDRESS_TAG: &str = "wallet_address";
+/// The timeout applied to alert webhook requests
+const ALERT_WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------
+// | Types |
+// ---------
+
+/// The configuration of the gas balance monitor
+#[derive(Clone, Debug)]
+pub struct GasBalanceMonitorConfig {
+    /// The ETH balance beneath which an alert is raised
+    pub threshold: f64,
+    /// The interval at which balances are checked
+    pub poll_interval: Duration,
+    /// The webhook to notify of low balances, if any
+    pub webhook_url: Option<String>,
+}
+
+/// The payload sent to the alert webhook for a low gas wallet balance
+#[derive(Debug, Serialize)]
+struct LowGasBalanceAlert {
+    /// The address of the gas wallet
+    address: String,
+    /// The peer the gas wallet is assigned to, if any
+    peer_id: Option<String>,
+    /// The wallet's ETH balance
+    balance: f64,
+    /// The configured alert threshold
+    threshold: f64,
+}
+
+/// Periodically checks the balances of active gas wallets
+#[derive(Clone)]
+pub struct GasBalanceMonitor {
+    /// The monitor's configuration
+    config: GasBalanceMonitorConfig,
+    /// The custody client, used to look up gas wallets and balances
+    custody_client: CustodyClient,
+    /// The HTTP client used to send webhook alerts
+    http_client: Arc<Client>,
+}
+
+impl GasBalanceMonitor {
+    /// Constructor
+    pub fn new(config: GasBalanceMonitorConfig, custody_client: CustodyClient) -> Self {
+        Self { config, custody_client, http_client: Arc::new(Client::new()) }
+    }
+
+    /// Spawn the monitor in a background task
+    pub fn spawn(self) -> JoinHandle<()> {
+        info!(
+            "Monitoring gas wallet balances every {:?} with a threshold of {} ETH",
+            self.config.poll_interval, self.config.threshold
+        );
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.poll_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.check_balances().await {
+                    error!("Error checking gas wallet balances: {e}");
+                }
+            }
+        })
+    }
+
+    /// Check the balance of each active gas wallet, alerting on those beneath
+    /// the threshold
+    async fn check_balances(&self) -> Result<(), FundsManagerError> {
+        let wallets = self.custody_client.get_active_gas_wallets().await?;
+        for wallet in wallets {
+            let balance = match self.custody_client.get_ether_balance(&wallet.address).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    warn!("Error fetching balance of gas wallet {}: {e}", wallet.address);
+                    continue;
+                },
+            };
+
+            let labels = [(WALLET_ADDRESS_TAG.to_string(), wallet.address.clone())];
+            metrics::gauge!(GAS_WALLET_BALANCE_METRIC, &labels).set(balance);
+            if balance >= self.config.threshold {
+                continue;
+            }
+
+            warn!(
+                "Gas wallet {} balance of {balance} ETH is below threshold of {} ETH",
+                wallet.address, self.config.threshold
+            );
+            metrics::counter!(GAS_WALLET_LOW_BALANCE_METRIC, &labels).increment(1);
+
+            let alert = LowGasBalanceAlert {
+                address: wallet.address,
+                peer_id: wallet.peer_id,
+                balance,
+                threshold: self.config.threshold,
+            };
+            self.send_alert(&alert).await;
+        }
+
+        Ok(())
+    }
+
+    /// Send a low balance alert to the configured webhook, if any
+    async fn send_alert(&self, alert: &LowGasBalanceAlert) {
+        let url = match &self.config.webhook_url {
+            Some(url) => url,
+            None => return,
+        };
+
+        let res = self
+            .http_client
+            .post(url)
+            .timeout(ALERT_WEBHOOK_TIMEOUT)
+            .json(alert)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        if let Err(e) = res {
+            warn!("Error sending low gas balance alert for {}: {e}", alert.address);
+        }
+    }
+}
@@ -1,9 +1,15 @@
 //! Route handlers for the funds manager
 
-use crate::custody_client::DepositWithdrawSource;
+use crate::custody_client::withdraw::WithdrawalReservation;
+use crate::custody_client::{units_to_f64, DepositWithdrawSource, ETHER_DECIMALS, WETH_TICKER};
+use crate::db::models::Swap;
 use crate::error::ApiError;
+use crate::execution_client::{ExecutionClient, EXECUTION_VENUE_NAME};
 use crate::Server;
+use bigdecimal::BigDecimal;
 use bytes::Bytes;
+use ethers::types::{TransactionReceipt, U256};
+use ethers::utils::format_units;
 use funds_manager_api::fees::{FeeWalletsResponse, WithdrawFeeBalanceRequest};
 use funds_manager_api::gas::{
     CreateGasWalletResponse, RefillGasRequest, RegisterGasWalletRequest, RegisterGasWalletResponse,
@@ -11,27 +17,54 @@ use funds_manager_api::gas::{
 };
 use funds_manager_api::hot_wallets::{
     CreateHotWalletRequest, CreateHotWalletResponse, HotWalletBalancesResponse,
-    TransferToVaultRequest, WithdrawToHotWalletRequest,
+    SetWithdrawalLimitRequest, TransferToVaultRequest, WalletWithBalances,
+    WithdrawToHotWalletRequest, WrapEtherRequest,
 };
 use funds_manager_api::quoters::{
-    DepositAddressResponse, ExecuteSwapRequest, ExecuteSwapResponse, GetExecutionQuoteRequest,
-    GetExecutionQuoteResponse, WithdrawFundsRequest,
+    DepositAddressResponse, EstimateSwapGasRequest, EstimateSwapGasResponse, ExecuteSwapRequest,
+    ExecuteSwapResponse, ExecutionQuote, GetExecutionQuoteRequest, GetExecutionQuoteResponse,
+    ListSwapsResponse, SwapRecord, WithdrawFundsRequest,
 };
+use funds_manager_api::vaults::{VaultBalancesResponse, VaultTransferRequest};
+use funds_manager_api::WithdrawAmount;
 use itertools::Itertools;
+use renegade_common::types::token::Token;
 use serde_json::json;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
-use tracing::warn;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 use warp::reply::Json;
 
 /// The "mints" query param
 pub const MINTS_QUERY_PARAM: &str = "mints";
+/// The "mint" query param
+pub const MINT_QUERY_PARAM: &str = "mint";
+/// The "include_usd" query param
+pub const INCLUDE_USD_QUERY_PARAM: &str = "include_usd";
+/// The "offset" query param
+pub const OFFSET_QUERY_PARAM: &str = "offset";
+/// The "limit" query param
+pub const LIMIT_QUERY_PARAM: &str = "limit";
+/// The default number of swaps returned in a page
+pub const DEFAULT_SWAPS_PAGE_SIZE: i64 = 100;
+/// The maximum number of swaps returned in a page
+pub const MAX_SWAPS_PAGE_SIZE: i64 = 1000;
+/// The ticker of the token used to price gas in USD
+pub const GAS_PRICE_TICKER: &str = "WETH";
 /// The asset used for gas (ETH)
 pub const GAS_ASSET_NAME: &str = "ETH";
 /// The maximum amount of gas that can be withdrawn at a given time
 pub const MAX_GAS_WITHDRAWAL_AMOUNT: f64 = 1.; // ETH
+/// The amount of wei left in the gas wallet to pay for the transfer when
+/// withdrawing its full balance
+pub const GAS_WITHDRAWAL_FEE_RESERVE_WEI: u64 = 100_000_000_000_000; // 0.0001 ETH
 /// The maximum amount that a request may refill gas to
 pub const MAX_GAS_REFILL_AMOUNT: f64 = 0.1; // ETH
+/// The duration for which a gas refill's idempotency key is retained
+pub const GAS_REFILL_IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 /// The maximum value of a quoter withdrawal that can be processed in a single
 /// request
 pub const MAX_WITHDRAWAL_VALUE: f64 = 50_000.; // USD
@@ -93,47 +126,103 @@ pub(crate) async fn quoter_withdraw_handler(
     withdraw_request: WithdrawFundsRequest,
     server: Arc<Server>,
 ) -> Result<Json, warp::Rejection> {
-    // Get the price of the token
-    let maybe_price = server
-        .relayer_client
-        .get_binance_price(&withdraw_request.mint)
-        .await
-        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
-
-    if let Some(price) = maybe_price {
-        // If a price was found, check that the withdrawal value is less than the
-        // allowable maximum. If no price was found, we do not block the
-        // withdrawal.
+    let vault = DepositWithdrawSource::Quoter.vault_name();
+    let hot_wallet = server.custody_client.get_hot_wallet_by_vault(vault).await?;
 
-        let value = withdraw_request.amount * price;
-        if value > MAX_WITHDRAWAL_VALUE {
+    // Resolve the amount to withdraw, then get the value of the withdrawal. The
+    // full balance is withdrawn in base units, its float value is only used for
+    // the value and limit checks
+    let (amount, amount_units) = match withdraw_request.amount {
+        WithdrawAmount::Amount(amount) => (amount, None),
+        WithdrawAmount::Max => {
+            let (balance, decimals) = server
+                .custody_client
+                .get_erc20_balance_units(&withdraw_request.mint, &hot_wallet.address)
+                .await
+                .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+            check_nonzero_withdrawal(balance)?;
+            let amount = units_to_f64(balance, decimals)
+                .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+            (amount, Some(balance))
+        },
+    };
+    let maybe_value = get_withdrawal_value(&withdraw_request.mint, amount, &server).await?;
+
+    // If a price was found, check that the withdrawal value is less than the
+    // allowable maximum. If no price was found, we do not block the withdrawal
+    // here, though it is rejected below if the wallet has a daily limit
+    match maybe_value {
+        Some(value) if value > MAX_WITHDRAWAL_VALUE => {
             return Err(warp::reject::custom(ApiError::BadRequest(format!(
                 "Requested withdrawal of ${} of {} exceeds maximum allowed withdrawal of ${}",
                 value, withdraw_request.mint, MAX_WITHDRAWAL_VALUE
             ))));
-        }
-    } else {
-        warn!("No price found for {}, allowing withdrawal", withdraw_request.mint);
+        },
+        Some(_) => {},
+        None => warn!("No price found for {}, allowing withdrawal", withdraw_request.mint),
     }
 
-    server
-        .custody_client
-        .withdraw_from_hot_wallet(
-            DepositWithdrawSource::Quoter,
-            &withdraw_request.address,
-            &withdraw_request.mint,
-            withdraw_request.amount,
-        )
-        .await
-        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    let reservation = reserve_withdrawal(
+        &hot_wallet.address,
+        &withdraw_request.mint,
+        amount,
+        maybe_value,
+        &server,
+    )
+    .await?;
+    let source = DepositWithdrawSource::Quoter;
+    let res = match amount_units {
+        Some(units) => {
+            server
+                .custody_client
+                .withdraw_units_from_hot_wallet(
+                    source,
+                    &withdraw_request.address,
+                    &withdraw_request.mint,
+                    units,
+                )
+                .await
+        },
+        None => {
+            server
+                .custody_client
+                .withdraw_from_hot_wallet(
+                    source,
+                    &withdraw_request.address,
+                    &withdraw_request.mint,
+                    amount,
+                )
+                .await
+        },
+    };
+    if let Err(e) = res {
+        release_withdrawal(reservation, &server).await;
+        return Err(warp::reject::custom(ApiError::InternalError(e.to_string())));
+    }
 
     Ok(warp::reply::json(&"Withdrawal complete"))
 }
 
 /// Handler for retrieving the address to deposit custody funds to
+///
+/// If a mint is given, it must resolve to a Fireblocks asset so that deposits
+/// of unsupported assets are not sent to a dead end
 pub(crate) async fn get_deposit_address_handler(
+    query_params: HashMap<String, String>,
     server: Arc<Server>,
 ) -> Result<Json, warp::Rejection> {
+    if let Some(mint) = query_params.get(MINT_QUERY_PARAM) {
+        let asset_id = server
+            .custody_client
+            .get_asset_id_for_address(mint)
+            .await
+            .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+        if asset_id.is_none() {
+            let msg = format!("Unsupported asset: {mint}");
+            return Err(warp::reject::custom(ApiError::BadRequest(msg)));
+        }
+    }
+
     let address = server
         .custody_client
         .get_deposit_address(DepositWithdrawSource::Quoter)
@@ -167,12 +256,83 @@ pub(crate) async fn execute_swap_handler(
     req: ExecuteSwapRequest,
     server: Arc<Server>,
 ) -> Result<Json, warp::Rejection> {
+    // Check the quoted price against the price reporter before executing
+    check_price_deviation(&req.quote, &server).await?;
+
     let vault = DepositWithdrawSource::Quoter.vault_name();
     let hot_wallet = server.custody_client.get_hot_wallet_by_vault(vault).await?;
     let wallet = server.custody_client.get_hot_wallet_private_key(&hot_wallet.address).await?;
 
-    let tx = server.execution_client.execute_swap(req.quote, &wallet).await?;
-    let resp = ExecuteSwapResponse { tx_hash: format!("{tx:#x}") };
+    let receipt = server.execution_client.execute_swap(req.quote.clone(), &wallet).await?;
+    let dry_run = server.execution_client.is_dry_run();
+    if !dry_run {
+        record_swap(&req.quote, &receipt, vault, &server).await;
+    }
+
+    let resp = ExecuteSwapResponse { tx_hash: format!("{:#x}", receipt.transaction_hash), dry_run };
+    Ok(warp::reply::json(&resp))
+}
+
+/// Handler for listing executed swaps
+pub(crate) async fn list_swaps_handler(
+    _body: Bytes, // unused
+    query_params: HashMap<String, String>,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    let offset = parse_query_param(&query_params, OFFSET_QUERY_PARAM, 0 /* default */)?;
+    let limit = parse_query_param(&query_params, LIMIT_QUERY_PARAM, DEFAULT_SWAPS_PAGE_SIZE)?;
+    if offset < 0 || !(1..=MAX_SWAPS_PAGE_SIZE).contains(&limit) {
+        return Err(warp::reject::custom(ApiError::BadRequest(format!(
+            "offset must be non-negative and limit must be between 1 and {MAX_SWAPS_PAGE_SIZE}"
+        ))));
+    }
+
+    let swaps = server
+        .custody_client
+        .get_swaps(offset, limit)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    let swaps = swaps.into_iter().map(swap_to_record).collect();
+    Ok(warp::reply::json(&ListSwapsResponse { swaps }))
+}
+
+/// Handler for estimating the gas cost of a swap without executing it
+pub(crate) async fn estimate_swap_gas_handler(
+    _body: Bytes, // no body
+    req: EstimateSwapGasRequest,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    // Fetch a quote on the quoter hot wallet to build the swap tx from
+    let vault = DepositWithdrawSource::Quoter.vault_name();
+    let hot_wallet = server.custody_client.get_hot_wallet_by_vault(vault).await?;
+    let wallet = server.custody_client.get_hot_wallet_private_key(&hot_wallet.address).await?;
+    let quote = server
+        .execution_client
+        .get_quote(req.buy_token_address, req.sell_token_address, req.sell_amount, &wallet)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    let (gas, base_fee) = server.execution_client.estimate_swap_gas(quote).await?;
+    let estimated_gas = u64::try_from(gas)
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    let cost_wei = gas.saturating_mul(base_fee);
+    let cost_eth = format_units(cost_wei, "ether")
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?
+        .parse::<f64>()
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    // Price the gas cost in USD, this is best effort
+    let weth_mint = Token::from_ticker(GAS_PRICE_TICKER).get_addr();
+    let cost_usd = match server.relayer_client.get_binance_price(&weth_mint).await {
+        Ok(price) => price.map(|p| p * cost_eth),
+        Err(e) => {
+            warn!("Error fetching ETH price for gas estimate: {e}");
+            None
+        },
+    };
+
+    let resp = EstimateSwapGasResponse { estimated_gas, cost_eth, cost_usd };
     Ok(warp::reply::json(&resp))
 }
 
@@ -183,18 +343,39 @@ pub(crate) async fn withdraw_gas_handler(
     withdraw_request: WithdrawGasRequest,
     server: Arc<Server>,
 ) -> Result<Json, warp::Rejection> {
-    if withdraw_request.amount > MAX_GAS_WITHDRAWAL_AMOUNT {
+    // Resolve the amount to withdraw, leaving enough in the wallet to pay for
+    // the transfer itself when withdrawing the full balance
+    let (amount, amount_wei) = match withdraw_request.amount {
+        WithdrawAmount::Amount(amount) => (amount, None),
+        WithdrawAmount::Max => {
+            let vault = DepositWithdrawSource::Gas.vault_name();
+            let gas_wallet = server.custody_client.get_hot_wallet_by_vault(vault).await?;
+            let balance = server
+                .custody_client
+                .get_ether_balance_wei(&gas_wallet.address)
+                .await
+                .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+            let amount_wei = balance.saturating_sub(U256::from(GAS_WITHDRAWAL_FEE_RESERVE_WEI));
+            check_nonzero_withdrawal(amount_wei)?;
+            let amount = units_to_f64(amount_wei, ETHER_DECIMALS)
+                .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+            (amount, Some(amount_wei))
+        },
+    };
+
+    if amount > MAX_GAS_WITHDRAWAL_AMOUNT {
         return Err(warp::reject::custom(ApiError::BadRequest(format!(
             "Requested amount {} ETH exceeds maximum allowed withdrawal of {} ETH",
-            withdraw_request.amount, MAX_GAS_WITHDRAWAL_AMOUNT
+            amount, MAX_GAS_WITHDRAWAL_AMOUNT
         ))));
     }
 
-    server
-        .custody_client
-        .withdraw_gas(withdraw_request.amount, &withdraw_request.destination_address)
-        .await
-        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    let destination = &withdraw_request.destination_address;
+    let res = match amount_wei {
+        Some(wei) => server.custody_client.withdraw_gas_wei(wei, destination).await,
+        None => server.custody_client.withdraw_gas(amount, destination).await,
+    };
+    res.map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
     Ok(warp::reply::json(&"Withdrawal complete"))
 }
 
@@ -211,11 +392,80 @@ pub(crate) async fn refill_gas_handler(
         ))));
     }
 
-    server.custody_client.refill_gas_wallets(req.amount).await?;
+    // Replay the prior result if the request has already been processed
+    let idempotency_key = req.idempotency_key.as_deref();
+    if let Some(key) = idempotency_key {
+        if let Some(prior) = claim_gas_refill_key(key, req.amount, &server).await? {
+            return Ok(warp::reply::json(&prior));
+        }
+    }
+
+    let res = server.custody_client.refill_gas_wallets(req.amount).await;
     let resp = json!({});
+    if let Some(key) = idempotency_key {
+        finalize_gas_refill(key, res.is_ok(), &resp, &server).await;
+    }
+
+    res?;
     Ok(warp::reply::json(&resp))
 }
 
+/// Claim the idempotency key of a gas refill request
+///
+/// Returns the prior response if a refill with the key has already completed
+async fn claim_gas_refill_key(
+    key: &str,
+    amount: f64,
+    server: &Server,
+) -> Result<Option<serde_json::Value>, warp::Rejection> {
+    let expired_before = SystemTime::now() - GAS_REFILL_IDEMPOTENCY_TTL;
+    let prior = server
+        .custody_client
+        .claim_gas_refill_key(key, amount, expired_before)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    let prior = match prior {
+        Some(prior) => prior,
+        None => return Ok(None),
+    };
+
+    if prior.amount != amount {
+        return Err(warp::reject::custom(ApiError::BadRequest(format!(
+            "idempotency key {key} was used for a refill of {} ETH",
+            prior.amount
+        ))));
+    }
+
+    match prior.response {
+        Some(response) => {
+            info!("Replaying gas refill for idempotency key {key}");
+            let resp = serde_json::from_str(&response)
+                .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+            Ok(Some(resp))
+        },
+        None => Err(warp::reject::custom(ApiError::BadRequest(format!(
+            "gas refill with idempotency key {key} is in progress"
+        )))),
+    }
+}
+
+/// Record the outcome of a gas refill against its idempotency key
+///
+/// A failed refill releases the key so that the request may be retried. The
+/// refill has already been attempted at this point, so a failure is logged
+/// rather than surfaced to the caller
+async fn finalize_gas_refill(key: &str, success: bool, resp: &serde_json::Value, server: &Server) {
+    let res = if success {
+        server.custody_client.complete_gas_refill(key, &resp.to_string()).await
+    } else {
+        server.custody_client.release_gas_refill_key(key).await
+    };
+
+    if let Err(e) = res {
+        error!("Failed to record gas refill for idempotency key {key}: {e}");
+    }
+}
+
 /// Handler for creating a new gas wallet
 pub(crate) async fn create_gas_wallet_handler(
     _body: Bytes, // no body
@@ -287,13 +537,21 @@ pub(crate) async fn get_hot_wallet_balances_handler(
         .map(|s| s.split(',').map(String::from).collect_vec())
         .unwrap_or_default();
 
-    let wallets = server
+    let include_usd = query_params.get(INCLUDE_USD_QUERY_PARAM).is_some_and(|v| v == "true");
+
+    let mut wallets = server
         .custody_client
         .get_hot_wallet_balances(&mints)
         .await
         .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
 
-    let resp = HotWalletBalancesResponse { wallets };
+    let total_usd = if include_usd {
+        Some(add_usd_valuations(&mut wallets, &mints, &server).await)
+    } else {
+        None
+    };
+
+    let resp = HotWalletBalancesResponse { wallets, total_usd };
     Ok(warp::reply::json(&resp))
 }
 
@@ -311,15 +569,390 @@ pub(crate) async fn transfer_to_vault_handler(
     Ok(warp::reply::json(&"Transfer from hot wallet to vault initiated"))
 }
 
+/// Handler for wrapping native ETH held by a hot wallet into WETH
+pub(crate) async fn wrap_ether_handler(
+    req: WrapEtherRequest,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    validate_wrap_amount(req.amount)?;
+    let balance = server
+        .custody_client
+        .get_ether_balance(&req.hot_wallet_address)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    check_sufficient_balance(balance, req.amount)?;
+
+    server
+        .custody_client
+        .wrap_ether(&req.hot_wallet_address, req.amount)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    Ok(warp::reply::json(&"ETH wrapped successfully"))
+}
+
+/// Handler for unwrapping WETH held by a hot wallet into native ETH
+pub(crate) async fn unwrap_ether_handler(
+    req: WrapEtherRequest,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    validate_wrap_amount(req.amount)?;
+    let weth_mint = Token::from_ticker(WETH_TICKER).get_addr();
+    let balance = server
+        .custody_client
+        .get_erc20_balance(&weth_mint, &req.hot_wallet_address)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+    check_sufficient_balance(balance, req.amount)?;
+
+    server
+        .custody_client
+        .unwrap_ether(&req.hot_wallet_address, req.amount)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    Ok(warp::reply::json(&"WETH unwrapped successfully"))
+}
+
 /// Handler for withdrawing funds from a vault to its hot wallet
 pub(crate) async fn withdraw_from_vault_handler(
     req: WithdrawToHotWalletRequest,
     server: Arc<Server>,
 ) -> Result<Json, warp::Rejection> {
-    server
+    let source = DepositWithdrawSource::from_vault_name(&req.vault)
+        .map_err(|e| warp::reject::custom(ApiError::BadRequest(e.to_string())))?;
+    let hot_wallet = server.custody_client.get_hot_wallet_by_vault(source.vault_name()).await?;
+
+    let maybe_value = get_withdrawal_value(&req.mint, req.amount, &server).await?;
+    let reservation =
+        reserve_withdrawal(&hot_wallet.address, &req.mint, req.amount, maybe_value, &server)
+            .await?;
+
+    let res = server
         .custody_client
         .transfer_from_vault_to_hot_wallet(&req.vault, &req.mint, req.amount)
+        .await;
+    if let Err(e) = res {
+        release_withdrawal(reservation, &server).await;
+        return Err(warp::reject::custom(ApiError::InternalError(e.to_string())));
+    }
+
+    Ok(warp::reply::json(&"Withdrawal from vault to hot wallet initiated"))
+}
+
+/// Handler for setting the daily withdrawal limit of a hot wallet
+pub(crate) async fn set_withdrawal_limit_handler(
+    req: SetWithdrawalLimitRequest,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    // Check that the hot wallet exists
+    let address = &req.hot_wallet_address;
+    let exists = server
+        .custody_client
+        .hot_wallet_exists(address)
         .await
         .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
-    Ok(warp::reply::json(&"Withdrawal from vault to hot wallet initiated"))
+    if !exists {
+        let msg = format!("Hot wallet not found: {address}");
+        return Err(warp::reject::custom(ApiError::BadRequest(msg)));
+    }
+
+    let res = match req.daily_limit_usd {
+        Some(limit) if !limit.is_finite() || limit < 0. => {
+            return Err(warp::reject::custom(ApiError::BadRequest(format!(
+                "Invalid daily withdrawal limit: {limit}"
+            ))));
+        },
+        Some(limit) => server.custody_client.set_withdrawal_limit(address, limit).await,
+        None => server.custody_client.remove_withdrawal_limit(address).await,
+    };
+    res.map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    Ok(warp::reply::json(&"Withdrawal limit updated"))
+}
+
+// --- Vaults --- //
+
+/// Handler for listing all vaults with their balances
+pub(crate) async fn get_vault_balances_handler(
+    _body: Bytes, // unused
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    let vaults = server
+        .custody_client
+        .get_vault_balances()
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    Ok(warp::reply::json(&VaultBalancesResponse { vaults }))
+}
+
+/// Handler for transferring funds directly between two vaults
+pub(crate) async fn transfer_between_vaults_handler(
+    req: VaultTransferRequest,
+    server: Arc<Server>,
+) -> Result<Json, warp::Rejection> {
+    // Validate the vault names before initiating the transfer
+    for vault in [&req.source_vault, &req.destination_vault] {
+        DepositWithdrawSource::from_vault_name(vault)
+            .map_err(|e| warp::reject::custom(ApiError::BadRequest(e.to_string())))?;
+    }
+
+    server
+        .custody_client
+        .transfer_between_vaults(&req.source_vault, &req.destination_vault, &req.mint, req.amount)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    Ok(warp::reply::json(&"Transfer between vaults complete"))
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Check that the amount resolved for a full-balance withdrawal is positive
+fn check_nonzero_withdrawal(amount: U256) -> Result<(), warp::Rejection> {
+    if amount.is_zero() {
+        let msg = "Nothing to withdraw, balance is empty".to_string();
+        return Err(warp::reject::custom(ApiError::BadRequest(msg)));
+    }
+
+    Ok(())
+}
+
+/// Validate the amount of a wrap or unwrap request
+fn validate_wrap_amount(amount: f64) -> Result<(), warp::Rejection> {
+    if !amount.is_finite() || amount <= 0. {
+        let msg = format!("Invalid amount: {amount}");
+        return Err(warp::reject::custom(ApiError::BadRequest(msg)));
+    }
+
+    Ok(())
+}
+
+/// Check that a wallet's balance covers the requested amount
+fn check_sufficient_balance(balance: f64, amount: f64) -> Result<(), warp::Rejection> {
+    if balance < amount {
+        let msg = format!("Insufficient balance. Available: {balance}, Requested: {amount}");
+        return Err(warp::reject::custom(ApiError::BadRequest(msg)));
+    }
+
+    Ok(())
+}
+
+/// Get the USD price of a token, if one is available
+async fn get_usd_price(mint: &str, server: &Server) -> Result<Option<f64>, warp::Rejection> {
+    server
+        .relayer_client
+        .get_binance_price(mint)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))
+}
+
+/// Value the given hot wallet balances in USD
+///
+/// Balances in tokens without a price are left unvalued and excluded from the
+/// totals. Returns the total USD value across all wallets
+async fn add_usd_valuations(
+    wallets: &mut [WalletWithBalances],
+    mints: &[String],
+    server: &Server,
+) -> f64 {
+    // Fetch each mint's price once
+    let mut prices = HashMap::new();
+    for mint in mints.iter() {
+        match get_usd_price(mint, server).await {
+            Ok(Some(price)) => {
+                prices.insert(mint.clone(), price);
+            },
+            Ok(None) => warn!("No price available for {mint}"),
+            Err(e) => warn!("Error fetching price for {mint}: {e:?}"),
+        }
+    }
+
+    let mut total = 0.;
+    for wallet in wallets.iter_mut() {
+        let mut wallet_total = 0.;
+        for balance in wallet.balances.iter_mut() {
+            balance.usd_value = prices.get(&balance.mint).map(|price| {
+                let amount = Token::from_addr(&balance.mint).convert_to_decimal(balance.amount);
+                amount * price
+            });
+            wallet_total += balance.usd_value.unwrap_or_default();
+        }
+
+        wallet.total_usd = Some(wallet_total);
+        total += wallet_total;
+    }
+
+    total
+}
+
+/// Get the USD value of a withdrawal, if a price is available for the mint
+async fn get_withdrawal_value(
+    mint: &str,
+    amount: f64,
+    server: &Server,
+) -> Result<Option<f64>, warp::Rejection> {
+    let maybe_price = get_usd_price(mint, server).await?;
+    Ok(maybe_price.map(|price| amount * price))
+}
+
+/// Reserve a withdrawal against the hot wallet's daily withdrawal limit
+///
+/// Returns the id of the recorded withdrawal, if one was recorded, which must
+/// be released if the transfer fails
+async fn reserve_withdrawal(
+    wallet_address: &str,
+    mint: &str,
+    amount: f64,
+    value: Option<f64>,
+    server: &Server,
+) -> Result<Option<Uuid>, warp::Rejection> {
+    let reservation = server
+        .custody_client
+        .reserve_withdrawal(wallet_address, mint, amount, value)
+        .await
+        .map_err(|e| warp::reject::custom(ApiError::InternalError(e.to_string())))?;
+
+    match reservation {
+        WithdrawalReservation::Reserved(id) => Ok(Some(id)),
+        WithdrawalReservation::Unrecorded => Ok(None),
+        WithdrawalReservation::Rejected(msg) => {
+            Err(warp::reject::custom(ApiError::BadRequest(msg)))
+        },
+    }
+}
+
+/// Release a reserved withdrawal after its transfer failed
+///
+/// A failure is logged rather than surfaced to the caller, in favor of the
+/// transfer error
+async fn release_withdrawal(reservation: Option<Uuid>, server: &Server) {
+    let id = match reservation {
+        Some(id) => id,
+        None => return,
+    };
+
+    if let Err(e) = server.custody_client.release_withdrawal(id).await {
+        error!("Failed to release withdrawal {id}: {e}");
+    }
+}
+
+/// Parse an integer query param, falling back to a default if it is absent
+fn parse_query_param(
+    query_params: &HashMap<String, String>,
+    name: &str,
+    default: i64,
+) -> Result<i64, warp::Rejection> {
+    match query_params.get(name) {
+        Some(value) => value.parse::<i64>().map_err(|_| {
+            warp::reject::custom(ApiError::BadRequest(format!("invalid {name}: {value}")))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Check that a quote's price is within the configured deviation of the price
+/// reporter's price for the pair
+///
+/// If either token cannot be priced, the quote is rejected
+async fn check_price_deviation(
+    quote: &ExecutionQuote,
+    server: &Server,
+) -> Result<(), warp::Rejection> {
+    let quoted_price = quote.price.parse::<f64>().map_err(|e| {
+        warp::reject::custom(ApiError::BadRequest(format!("invalid quoted price: {e}")))
+    })?;
+
+    // The quoted price is in units of the buy token per unit of the sell token
+    let sell_mint = format!("{:#x}", quote.sell_token_address);
+    let buy_mint = format!("{:#x}", quote.buy_token_address);
+    let sell_price = get_usd_price(&sell_mint, server).await?;
+    let buy_price = get_usd_price(&buy_mint, server).await?;
+    let reference_price = match (sell_price, buy_price) {
+        (Some(sell), Some(buy)) if buy > 0. => sell / buy,
+        _ => {
+            return Err(warp::reject::custom(ApiError::BadRequest(format!(
+                "No reference price for {sell_mint} -> {buy_mint}"
+            ))));
+        },
+    };
+
+    let deviation_pct = (quoted_price - reference_price).abs() / reference_price * 100.;
+    if deviation_pct > server.max_price_deviation_pct {
+        return Err(warp::reject::custom(ApiError::BadRequest(format!(
+            "Quoted price {quoted_price} deviates {deviation_pct:.2}% from reference price \
+             {reference_price}, exceeding maximum of {}%",
+            server.max_price_deviation_pct
+        ))));
+    }
+
+    Ok(())
+}
+
+/// Record an executed swap in the database
+///
+/// The swap has already settled at this point, so a failure is logged rather
+/// than surfaced to the caller
+async fn record_swap(
+    quote: &ExecutionQuote,
+    receipt: &TransactionReceipt,
+    source: &str,
+    server: &Server,
+) {
+    let buy_amount_actual =
+        ExecutionClient::get_amount_received(receipt, quote.buy_token_address, quote.from);
+    let gas_cost = ExecutionClient::get_gas_cost(receipt);
+    let quoted_price = match BigDecimal::from_str(&quote.price) {
+        Ok(price) => price,
+        Err(e) => {
+            error!("Failed to parse quoted price {}: {e}", quote.price);
+            return;
+        },
+    };
+
+    let swap = Swap {
+        id: Uuid::new_v4(),
+        venue: EXECUTION_VENUE_NAME.to_string(),
+        source: source.to_string(),
+        sell_token: format!("{:#x}", quote.sell_token_address),
+        buy_token: format!("{:#x}", quote.buy_token_address),
+        sell_amount: u256_to_bigdecimal(quote.sell_amount),
+        quoted_price,
+        buy_amount_actual: u256_to_bigdecimal(buy_amount_actual),
+        gas_cost: u256_to_bigdecimal(gas_cost),
+        tx_hash: format!("{:#x}", receipt.transaction_hash),
+        created_at: SystemTime::now(),
+    };
+
+    if let Err(e) = server.custody_client.insert_swap(swap).await {
+        error!("Failed to record swap {:#x}: {e}", receipt.transaction_hash);
+    }
+}
+
+/// Convert a `U256` into a `BigDecimal`
+fn u256_to_bigdecimal(value: U256) -> BigDecimal {
+    // A decimal string is always a valid `BigDecimal`
+    BigDecimal::from_str(&value.to_string()).expect("invalid decimal string")
+}
+
+/// Convert a swap database record into its API representation
+fn swap_to_record(swap: Swap) -> SwapRecord {
+    let timestamp =
+        swap.created_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    SwapRecord {
+        id: swap.id,
+        venue: swap.venue,
+        source: swap.source,
+        sell_token: swap.sell_token,
+        buy_token: swap.buy_token,
+        sell_amount: swap.sell_amount.to_string(),
+        quoted_price: swap.quoted_price.to_string(),
+        buy_amount_actual: swap.buy_amount_actual.to_string(),
+        gas_cost: swap.gas_cost.to_string(),
+        tx_hash: swap.tx_hash,
+        timestamp,
+    }
 }
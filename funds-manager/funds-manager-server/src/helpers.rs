@@ -3,7 +3,10 @@
 
 use aws_config::SdkConfig;
 use aws_sdk_secretsmanager::client::Client as SecretsManagerClient;
-use ethers::{contract::abigen, types::H256};
+use ethers::{
+    contract::abigen,
+    types::{TransactionReceipt, H256},
+};
 use renegade_util::err_str;
 
 use crate::error::FundsManagerError;
@@ -11,6 +14,14 @@ use crate::error::FundsManagerError;
 /// A readable type alias for a transaction hash
 pub type TransactionHash = H256;
 
+/// Build the synthetic receipt returned in place of a transaction sent in
+/// dry-run mode
+///
+/// The receipt has a zero transaction hash
+pub fn dry_run_receipt() -> TransactionReceipt {
+    TransactionReceipt::default()
+}
+
 // ---------
 // | ERC20 |
 // ---------
@@ -28,6 +39,19 @@ abigen!(
     ]"#
 );
 
+// --------
+// | WETH |
+// --------
+
+// The WETH interface
+abigen!(
+    WETH,
+    r#"[
+        function deposit() external payable
+        function withdraw(uint256 wad) external
+    ]"#
+);
+
 // -----------------------
 // | AWS Secrets Manager |
 // -----------------------
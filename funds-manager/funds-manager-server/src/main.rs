@@ -11,6 +11,7 @@ pub mod db;
 pub mod error;
 pub mod execution_client;
 pub mod fee_indexer;
+pub mod gas_monitor;
 pub mod handlers;
 pub mod helpers;
 pub mod middleware;
@@ -27,21 +28,26 @@ use funds_manager_api::gas::{
     REFILL_GAS_ROUTE, REGISTER_GAS_WALLET_ROUTE, REPORT_ACTIVE_PEERS_ROUTE, WITHDRAW_GAS_ROUTE,
 };
 use funds_manager_api::hot_wallets::{
-    CreateHotWalletRequest, TransferToVaultRequest, WithdrawToHotWalletRequest,
-    TRANSFER_TO_VAULT_ROUTE, WITHDRAW_TO_HOT_WALLET_ROUTE,
+    CreateHotWalletRequest, SetWithdrawalLimitRequest, TransferToVaultRequest,
+    WithdrawToHotWalletRequest, WrapEtherRequest, SET_WITHDRAWAL_LIMIT_ROUTE,
+    TRANSFER_TO_VAULT_ROUTE, UNWRAP_ETHER_ROUTE, WITHDRAW_TO_HOT_WALLET_ROUTE, WRAP_ETHER_ROUTE,
 };
 use funds_manager_api::quoters::{
-    ExecuteSwapRequest, GetExecutionQuoteRequest, WithdrawFundsRequest, EXECUTE_SWAP_ROUTE,
-    GET_DEPOSIT_ADDRESS_ROUTE, GET_EXECUTION_QUOTE_ROUTE, WITHDRAW_CUSTODY_ROUTE,
+    EstimateSwapGasRequest, ExecuteSwapRequest, GetExecutionQuoteRequest, WithdrawFundsRequest,
+    ESTIMATE_SWAP_GAS_ROUTE, EXECUTE_SWAP_ROUTE, GET_DEPOSIT_ADDRESS_ROUTE,
+    GET_EXECUTION_QUOTE_ROUTE, LIST_SWAPS_ROUTE, WITHDRAW_CUSTODY_ROUTE,
 };
+use funds_manager_api::vaults::{VaultTransferRequest, TRANSFER_BETWEEN_VAULTS_ROUTE};
 use funds_manager_api::PING_ROUTE;
 use handlers::{
-    create_gas_wallet_handler, create_hot_wallet_handler, execute_swap_handler,
-    get_deposit_address_handler, get_execution_quote_handler, get_fee_wallets_handler,
-    get_hot_wallet_balances_handler, index_fees_handler, quoter_withdraw_handler,
-    redeem_fees_handler, refill_gas_handler, register_gas_wallet_handler,
-    report_active_peers_handler, transfer_to_vault_handler, withdraw_fee_balance_handler,
-    withdraw_from_vault_handler, withdraw_gas_handler,
+    create_gas_wallet_handler, create_hot_wallet_handler, estimate_swap_gas_handler,
+    execute_swap_handler, get_deposit_address_handler, get_execution_quote_handler,
+    get_fee_wallets_handler, get_hot_wallet_balances_handler, get_vault_balances_handler,
+    index_fees_handler, list_swaps_handler, quoter_withdraw_handler, redeem_fees_handler,
+    refill_gas_handler, register_gas_wallet_handler, report_active_peers_handler,
+    set_withdrawal_limit_handler, transfer_between_vaults_handler, transfer_to_vault_handler,
+    unwrap_ether_handler, withdraw_fee_balance_handler, withdraw_from_vault_handler,
+    withdraw_gas_handler, wrap_ether_handler,
 };
 use middleware::{identity, with_hmac_auth, with_json_body};
 use renegade_util::telemetry::configure_telemetry;
@@ -56,6 +62,8 @@ use tracing::{error, warn};
 
 use crate::custody_client::CustodyClient;
 use crate::error::ApiError;
+use crate::execution_client::gas_pricing::GasPricingMode;
+use crate::execution_client::DEFAULT_SWAP_CONFIRMATIONS;
 
 // -------
 // | Cli |
@@ -90,6 +98,12 @@ struct Cli {
     /// redemption
     #[clap(long, env = "USDC_MINT")]
     usdc_mint: String,
+    /// The minimum USD value of a fee for it to be redeemed
+    ///
+    /// Fees below this value are skipped until they accumulate enough value to
+    /// justify the gas cost of redemption
+    #[clap(long, env = "MIN_REDEMPTION_VALUE_USD", default_value = "0.0")]
+    min_redemption_value_usd: f64,
 
     // --- Decryption Keys --- //
 
@@ -117,12 +131,39 @@ struct Cli {
     /// The fireblocks api secret
     #[clap(long, env = "FIREBLOCKS_API_SECRET")]
     fireblocks_api_secret: String,
+    /// The time, in seconds, to wait for a Fireblocks transaction to complete
+    #[clap(long, env = "FIREBLOCKS_POLL_TIMEOUT_SECS", default_value = "60")]
+    fireblocks_poll_timeout_secs: u64,
+    /// The interval, in seconds, at which to poll a Fireblocks transaction
+    #[clap(long, env = "FIREBLOCKS_POLL_INTERVAL_SECS", default_value = "5")]
+    fireblocks_poll_interval_secs: u64,
     /// The execution venue api key
     #[clap(long, env = "EXECUTION_VENUE_API_KEY")]
     execution_venue_api_key: String,
     /// The execution venue base url
     #[clap(long, env = "EXECUTION_VENUE_BASE_URL")]
     execution_venue_base_url: String,
+    /// The maximum percentage by which a quote's price may deviate from the
+    /// price reporter's price before execution is rejected
+    #[clap(long, env = "MAX_PRICE_DEVIATION_PCT", default_value = "2.0")]
+    max_price_deviation_pct: f64,
+    /// The number of confirmations to await for swap transactions
+    #[clap(long, env = "SWAP_CONFIRMATIONS", default_value_t = DEFAULT_SWAP_CONFIRMATIONS)]
+    swap_confirmations: usize,
+    /// The transaction type used to price gas for swaps
+    #[clap(long, env = "GAS_PRICING_MODE", value_enum, default_value = "eip1559")]
+    gas_pricing_mode: GasPricingMode,
+    /// The multiple of the latest base fee to use as the max fee for EIP-1559
+    /// swaps
+    ///
+    /// If omitted, fees are estimated by the RPC provider
+    #[clap(long, env = "BASE_FEE_MULTIPLIER")]
+    base_fee_multiplier: Option<f64>,
+    /// The gas price, in gwei, to use for legacy swaps
+    ///
+    /// If omitted, the gas price is estimated by the RPC provider
+    #[clap(long, env = "LEGACY_GAS_PRICE_GWEI")]
+    legacy_gas_price_gwei: Option<f64>,
 
     // --- Server Config --- //
 
@@ -132,6 +173,36 @@ struct Cli {
     /// Whether to enable datadog formatted logs
     #[clap(long, default_value = "false")]
     datadog_logging: bool,
+    /// Whether to run in dry-run mode
+    ///
+    /// In dry-run mode, transfers and swaps are validated and quoted but not
+    /// sent on-chain; a synthetic receipt with a zero tx hash is returned
+    #[clap(long, env = "DRY_RUN")]
+    dry_run: bool,
+
+    // --- Telemetry --- //
+
+    /// Whether or not to enable metrics collection
+    #[clap(long, env = "ENABLE_METRICS")]
+    metrics_enabled: bool,
+    /// The StatsD recorder host to send metrics to
+    #[clap(long, env = "STATSD_HOST", default_value = "127.0.0.1")]
+    statsd_host: String,
+    /// The StatsD recorder port to send metrics to
+    #[clap(long, env = "STATSD_PORT", default_value = "8125")]
+    statsd_port: u16,
+
+    // --- Gas Balance Monitoring --- //
+
+    /// The ETH balance beneath which an active gas wallet raises an alert
+    #[clap(long, env = "GAS_BALANCE_ALERT_THRESHOLD", default_value = "0.005")]
+    gas_balance_alert_threshold: f64,
+    /// The interval, in seconds, at which gas wallet balances are checked
+    #[clap(long, env = "GAS_BALANCE_POLL_INTERVAL_SECS", default_value = "300")]
+    gas_balance_poll_interval_secs: u64,
+    /// An optional webhook to notify when a gas wallet's balance is low
+    #[clap(long, env = "GAS_BALANCE_ALERT_WEBHOOK_URL")]
+    gas_balance_alert_webhook_url: Option<String>,
 }
 
 impl Cli {
@@ -165,19 +236,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
     if cli.hmac_key.is_none() {
         warn!("Authentication is disabled. This is not recommended for production use.");
     }
+    if cli.dry_run {
+        warn!("Dry-run mode is enabled, transfers and swaps will not be sent on-chain");
+    }
 
     configure_telemetry(
         cli.datadog_logging, // datadog_enabled
         false,               // otlp_enabled
-        false,               // metrics_enabled
+        cli.metrics_enabled, // metrics_enabled
         "".to_string(),      // collector_endpoint
-        "",                  // statsd_host
-        0,                   // statsd_port
+        &cli.statsd_host,    // statsd_host
+        cli.statsd_port,     // statsd_port
     )
     .expect("failed to setup telemetry");
 
     let port = cli.port; // copy `cli.port` to use after moving `cli`
     let server = Server::build_from_cli(cli).await.expect("failed to build server");
+    server.spawn_gas_balance_monitor();
 
     // ----------
     // | Routes |
@@ -234,6 +309,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .and(warp::path("custody"))
         .and(warp::path("quoters"))
         .and(warp::path(GET_DEPOSIT_ADDRESS_ROUTE))
+        .and(warp::query::<HashMap<String, String>>())
         .and(with_server(server.clone()))
         .and_then(get_deposit_address_handler);
 
@@ -257,6 +333,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .and(with_server(server.clone()))
         .and_then(execute_swap_handler);
 
+    let list_swaps = warp::get()
+        .and(warp::path("custody"))
+        .and(warp::path(LIST_SWAPS_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_server(server.clone()))
+        .and_then(list_swaps_handler);
+
+    let estimate_swap_gas = warp::get()
+        .and(warp::path("custody"))
+        .and(warp::path("quoters"))
+        .and(warp::path(ESTIMATE_SWAP_GAS_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .and(warp::query::<EstimateSwapGasRequest>())
+        .and(with_server(server.clone()))
+        .and_then(estimate_swap_gas_handler);
+
     // --- Gas --- //
 
     let withdraw_gas = warp::post()
@@ -345,6 +438,55 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .and(with_server(server.clone()))
         .and_then(withdraw_from_vault_handler);
 
+    let wrap_ether = warp::post()
+        .and(warp::path("custody"))
+        .and(warp::path("hot-wallets"))
+        .and(warp::path(WRAP_ETHER_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .map(with_json_body::<WrapEtherRequest>)
+        .and_then(identity)
+        .and(with_server(server.clone()))
+        .and_then(wrap_ether_handler);
+
+    let unwrap_ether = warp::post()
+        .and(warp::path("custody"))
+        .and(warp::path("hot-wallets"))
+        .and(warp::path(UNWRAP_ETHER_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .map(with_json_body::<WrapEtherRequest>)
+        .and_then(identity)
+        .and(with_server(server.clone()))
+        .and_then(unwrap_ether_handler);
+
+    let set_withdrawal_limit = warp::post()
+        .and(warp::path("custody"))
+        .and(warp::path("hot-wallets"))
+        .and(warp::path(SET_WITHDRAWAL_LIMIT_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .map(with_json_body::<SetWithdrawalLimitRequest>)
+        .and_then(identity)
+        .and(with_server(server.clone()))
+        .and_then(set_withdrawal_limit_handler);
+
+    // --- Vaults --- //
+
+    let get_vault_balances = warp::get()
+        .and(warp::path("custody"))
+        .and(warp::path("vaults"))
+        .and(with_hmac_auth(server.clone()))
+        .and(with_server(server.clone()))
+        .and_then(get_vault_balances_handler);
+
+    let transfer_between_vaults = warp::post()
+        .and(warp::path("custody"))
+        .and(warp::path("vaults"))
+        .and(warp::path(TRANSFER_BETWEEN_VAULTS_ROUTE))
+        .and(with_hmac_auth(server.clone()))
+        .map(with_json_body::<VaultTransferRequest>)
+        .and_then(identity)
+        .and(with_server(server.clone()))
+        .and_then(transfer_between_vaults_handler);
+
     let routes = ping
         .or(index_fees)
         .or(redeem_fees)
@@ -352,6 +494,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .or(get_deposit_address)
         .or(get_execution_quote)
         .or(execute_swap)
+        .or(estimate_swap_gas)
+        .or(list_swaps)
         .or(withdraw_gas)
         .or(refill_gas)
         .or(report_active_peers)
@@ -361,6 +505,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .or(withdraw_fee_balance)
         .or(transfer_to_vault)
         .or(transfer_to_hot_wallet)
+        .or(set_withdrawal_limit)
+        .or(wrap_ether)
+        .or(unwrap_ether)
+        .or(transfer_between_vaults)
+        .or(get_vault_balances)
         .or(get_hot_wallet_balances)
         .or(create_hot_wallet)
         .recover(handle_rejection);
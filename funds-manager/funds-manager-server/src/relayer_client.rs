@@ -50,19 +50,19 @@ const SIG_EXPIRATION_BUFFER_MS: u64 = 5000;
 pub struct RelayerClient {
     /// The base URL of the relayer
     base_url: String,
-    /// The mind of the USDC token
+    /// The mint of the USDC token, lowercased
     usdc_mint: String,
 }
 
 impl RelayerClient {
     /// Create a new relayer client
     pub fn new(base_url: &str, usdc_mint: &str) -> Self {
-        Self { base_url: base_url.to_string(), usdc_mint: usdc_mint.to_string() }
+        Self { base_url: base_url.to_string(), usdc_mint: usdc_mint.to_lowercase() }
     }
 
     /// Get the price for a given mint
     pub async fn get_binance_price(&self, mint: &str) -> Result<Option<f64>, FundsManagerError> {
-        if mint == self.usdc_mint {
+        if mint.to_lowercase() == self.usdc_mint {
             return Ok(Some(1.0));
         }
 
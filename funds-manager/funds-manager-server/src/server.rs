@@ -1,7 +1,7 @@
 //! Defines the server which encapsulates all dependencies for funds manager
 //! execution
 
-use std::{error::Error, str::FromStr, sync::Arc};
+use std::{error::Error, str::FromStr, sync::Arc, time::Duration};
 
 use aws_config::{BehaviorVersion, Region, SdkConfig};
 use ethers::signers::LocalWallet;
@@ -13,11 +13,12 @@ use renegade_circuit_types::elgamal::DecryptionKey;
 use renegade_util::raw_err_str;
 
 use crate::{
-    custody_client::CustodyClient,
+    custody_client::{CustodyClient, FireblocksPollConfig},
     db::{create_db_pool, DbPool},
     error::FundsManagerError,
-    execution_client::ExecutionClient,
+    execution_client::{gas_pricing::GasPricingStrategy, ExecutionClient},
     fee_indexer::Indexer,
+    gas_monitor::{GasBalanceMonitor, GasBalanceMonitorConfig},
     relayer_client::RelayerClient,
     Cli,
 };
@@ -60,6 +61,13 @@ pub(crate) struct Server {
     pub aws_config: SdkConfig,
     /// The HMAC key for custody endpoint authentication
     pub hmac_key: Option<[u8; 32]>,
+    /// The configuration of the gas wallet balance monitor
+    pub gas_balance_monitor_config: GasBalanceMonitorConfig,
+    /// The maximum percentage by which a quote's price may deviate from the
+    /// price reporter's price
+    pub max_price_deviation_pct: f64,
+    /// The minimum USD value of a fee for it to be redeemed
+    pub min_redemption_value_usd: f64,
 }
 
 impl Server {
@@ -97,6 +105,10 @@ impl Server {
         let db_pool = create_db_pool(&args.db_url).await?;
         let arc_pool = Arc::new(db_pool);
 
+        let poll_config = FireblocksPollConfig {
+            timeout: Duration::from_secs(args.fireblocks_poll_timeout_secs),
+            interval: Duration::from_secs(args.fireblocks_poll_interval_secs),
+        };
         let custody_client = CustodyClient::new(
             chain_id,
             args.fireblocks_api_key,
@@ -104,12 +116,28 @@ impl Server {
             args.rpc_url.clone(),
             arc_pool.clone(),
             config.clone(),
+            args.dry_run,
+            poll_config,
         );
 
+        let gas_balance_monitor_config = GasBalanceMonitorConfig {
+            threshold: args.gas_balance_alert_threshold,
+            poll_interval: Duration::from_secs(args.gas_balance_poll_interval_secs),
+            webhook_url: args.gas_balance_alert_webhook_url,
+        };
+
+        let gas_pricing = GasPricingStrategy::new(
+            args.gas_pricing_mode,
+            args.base_fee_multiplier,
+            args.legacy_gas_price_gwei,
+        )?;
         let execution_client = ExecutionClient::new(
             args.execution_venue_api_key,
             args.execution_venue_base_url,
             &args.rpc_url,
+            args.swap_confirmations,
+            args.dry_run,
+            gas_pricing,
         )?;
 
         Ok(Self {
@@ -123,9 +151,18 @@ impl Server {
             execution_client,
             aws_config: config,
             hmac_key,
+            gas_balance_monitor_config,
+            max_price_deviation_pct: args.max_price_deviation_pct,
+            min_redemption_value_usd: args.min_redemption_value_usd,
         })
     }
 
+    /// Spawn the gas wallet balance monitor in the background
+    pub fn spawn_gas_balance_monitor(&self) {
+        let config = self.gas_balance_monitor_config.clone();
+        GasBalanceMonitor::new(config, self.custody_client.clone()).spawn();
+    }
+
     /// Build an indexer
     pub fn build_indexer(&self) -> Result<Indexer, FundsManagerError> {
         Ok(Indexer::new(
@@ -137,6 +174,7 @@ impl Server {
             self.db_pool.clone(),
             self.relayer_client.clone(),
             self.custody_client.clone(),
+            self.min_redemption_value_usd,
         ))
     }
 }
@@ -0,0 +1,3 @@
+-- Drop the withdrawal limit tables
+DROP TABLE IF EXISTS withdrawals;
+DROP TABLE IF EXISTS withdrawal_limits;
@@ -0,0 +1,16 @@
+-- Create a table to store the daily withdrawal limit of each hot wallet
+CREATE TABLE withdrawal_limits (
+    wallet_address TEXT PRIMARY KEY,
+    daily_limit_usd FLOAT8 NOT NULL
+);
+
+-- Create a table to record withdrawals, used to compute rolling daily totals
+CREATE TABLE withdrawals (
+    id UUID PRIMARY KEY,
+    wallet_address TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    amount FLOAT8 NOT NULL,
+    usd_value FLOAT8 NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX withdrawals_wallet_address_created_at_idx ON withdrawals (wallet_address, created_at);
@@ -0,0 +1,2 @@
+-- Drop the swaps table
+DROP TABLE IF EXISTS swaps;
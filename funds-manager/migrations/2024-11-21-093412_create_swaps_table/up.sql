@@ -0,0 +1,15 @@
+-- Create a table to record executed swaps for reconciliation
+CREATE TABLE swaps (
+    id UUID PRIMARY KEY,
+    venue TEXT NOT NULL,
+    source TEXT NOT NULL,
+    sell_token TEXT NOT NULL,
+    buy_token TEXT NOT NULL,
+    sell_amount NUMERIC NOT NULL,
+    quoted_price NUMERIC NOT NULL,
+    buy_amount_actual NUMERIC NOT NULL,
+    gas_cost NUMERIC NOT NULL,
+    tx_hash TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX swaps_created_at_idx ON swaps (created_at);
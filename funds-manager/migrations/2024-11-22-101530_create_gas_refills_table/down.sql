@@ -0,0 +1,2 @@
+-- Drop the gas refills table
+DROP TABLE IF EXISTS gas_refills;
@@ -0,0 +1,7 @@
+-- Create a table to record idempotency keys of processed gas refills
+CREATE TABLE gas_refills (
+    idempotency_key TEXT PRIMARY KEY,
+    amount FLOAT8 NOT NULL,
+    response TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
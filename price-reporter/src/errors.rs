@@ -12,6 +12,8 @@ use renegade_price_reporter::errors::ExchangeConnectionError;
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum ServerError {
+    /// An error parsing the server's configuration
+    InvalidConfig(String),
     /// An error setting up the token remapping
     TokenRemap(String),
     /// An error attempting to subscribe to a price stream
@@ -19,6 +21,11 @@ pub enum ServerError {
     InvalidPairInfo(String),
     /// An error establishing a connection to an exchange
     ExchangeConnection(ExchangeConnectionError),
+    /// A price stream has not yet computed a price
+    PriceUnavailable(String),
+    /// A price stream has not received an update within its staleness
+    /// threshold
+    StalePriceStream(String),
     /// An error getting the peer address of a websocket connection
     GetPeerAddr(io::Error),
     /// An error establishing a websocket connection
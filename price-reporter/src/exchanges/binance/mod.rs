@@ -0,0 +1,262 @@
+//! Defines the connection to the Binance websocket API, streaming midpoint
+//! prices from the best bid and offer of a pair
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use futures_util::{
+    stream::{SplitSink, StreamExt},
+    SinkExt, Stream,
+};
+use renegade_common::types::{exchange::Exchange, token::Token, Price};
+use renegade_price_reporter::{
+    errors::ExchangeConnectionError, exchange::ExchangeConnection,
+    worker::ExchangeConnectionsConfig,
+};
+use renegade_util::err_str;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::error;
+use tungstenite::Message;
+
+// -------------
+// | Constants |
+// -------------
+
+/// The base URL of the Binance websocket API
+const BINANCE_WS_BASE_URL: &str = "wss://stream.binance.com:443/ws";
+/// The URL of the Binance exchange info endpoint
+const BINANCE_EXCHANGE_INFO_URL: &str = "https://api.binance.com/api/v3/exchangeInfo";
+/// The trading status of a symbol that is open for trading on Binance
+const BINANCE_TRADING_STATUS: &str = "TRADING";
+
+// ---------
+// | Types |
+// ---------
+
+/// A type alias for an item in the price stream
+type PriceStreamType = Result<Price, ExchangeConnectionError>;
+/// A type alias for the write half of the Binance websocket
+type BinanceWsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// A best bid/offer update streamed on the `@bookTicker` channel
+#[derive(Debug, Deserialize)]
+struct BookTickerMessage {
+    /// The best bid price
+    #[serde(rename = "b")]
+    best_bid: String,
+    /// The best ask price
+    #[serde(rename = "a")]
+    best_ask: String,
+}
+
+/// The response of the Binance exchange info endpoint
+#[derive(Debug, Deserialize)]
+struct ExchangeInfoResponse {
+    /// The symbols matching the request
+    symbols: Vec<SymbolInfo>,
+}
+
+/// The exchange info for a single symbol
+#[derive(Debug, Deserialize)]
+struct SymbolInfo {
+    /// The trading status of the symbol
+    status: String,
+}
+
+// --------------
+// | Connection |
+// --------------
+
+/// The connection to the Binance `@bookTicker` websocket channel
+pub struct BinanceConnection {
+    /// The stream of midpoint prices
+    price_stream: Box<dyn Stream<Item = PriceStreamType> + Unpin + Send>,
+    /// The write half of the websocket, used to send keepalive messages
+    write_stream: BinanceWsSink,
+}
+
+impl BinanceConnection {
+    /// Get the Binance symbol for a pair, e.g. `ETHUSDT`
+    ///
+    /// The symbol is built from Binance's tickers for the tokens, which may
+    /// differ from the Renegade tickers, e.g. `WETH` trades as `ETH`
+    fn symbol(base_token: &Token, quote_token: &Token) -> Result<String, ExchangeConnectionError> {
+        let base = base_token.get_exchange_ticker(Exchange::Binance).ok_or_else(|| {
+            ExchangeConnectionError::InvalidMessage(format!("no Binance ticker for {base_token}"))
+        })?;
+        let quote = quote_token.get_exchange_ticker(Exchange::Binance).ok_or_else(|| {
+            ExchangeConnectionError::InvalidMessage(format!("no Binance ticker for {quote_token}"))
+        })?;
+
+        Ok(Self::symbol_from_tickers(&base, &quote))
+    }
+
+    /// Get the Binance symbol for a pair of Binance tickers
+    fn symbol_from_tickers(base: &str, quote: &str) -> String {
+        format!("{base}{quote}").to_uppercase()
+    }
+
+    /// Get the websocket URL of the `@bookTicker` channel for a pair
+    fn websocket_url(
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Result<String, ExchangeConnectionError> {
+        let symbol = Self::symbol(base_token, quote_token)?.to_lowercase();
+        Ok(format!("{BINANCE_WS_BASE_URL}/{symbol}@bookTicker"))
+    }
+
+    /// Compute the midpoint price from a websocket message
+    ///
+    /// Returns `None` for messages that do not carry a book update, e.g.
+    /// control frames
+    fn midpoint_from_ws_message(
+        message: Message,
+    ) -> Result<Option<Price>, ExchangeConnectionError> {
+        let text = match message {
+            Message::Text(text) => text,
+            _ => return Ok(None),
+        };
+
+        let update: BookTickerMessage = serde_json::from_str(&text)
+            .map_err(err_str!(ExchangeConnectionError::InvalidMessage))?;
+        let best_bid: Price =
+            update.best_bid.parse().map_err(err_str!(ExchangeConnectionError::InvalidMessage))?;
+        let best_ask: Price =
+            update.best_ask.parse().map_err(err_str!(ExchangeConnectionError::InvalidMessage))?;
+
+        Ok(Some((best_bid + best_ask) / 2.0))
+    }
+}
+
+impl Stream for BinanceConnection {
+    type Item = PriceStreamType;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.price_stream.poll_next_unpin(cx)
+    }
+}
+
+#[async_trait]
+impl ExchangeConnection for BinanceConnection {
+    async fn connect(
+        base_token: Token,
+        quote_token: Token,
+        _config: &ExchangeConnectionsConfig,
+    ) -> Result<Self, ExchangeConnectionError>
+    where
+        Self: Sized,
+    {
+        let url = Self::websocket_url(&base_token, &quote_token)?;
+        let (ws_stream, _) = connect_async(url.as_str()).await.map_err(|e| {
+            error!("Cannot connect to {}: {e}", Exchange::Binance);
+            ExchangeConnectionError::HandshakeFailure(e.to_string())
+        })?;
+        let (write_stream, read_stream) = ws_stream.split();
+
+        // Map the websocket messages to midpoint prices, dropping messages that
+        // carry no price
+        let price_stream = read_stream.filter_map(|message| async move {
+            let message = match message.map_err(err_str!(ExchangeConnectionError::ConnectionHangup))
+            {
+                Ok(message) => message,
+                Err(e) => return Some(Err(e)),
+            };
+
+            BinanceConnection::midpoint_from_ws_message(message).transpose()
+        });
+
+        Ok(Self { price_stream: Box::new(Box::pin(price_stream)), write_stream })
+    }
+
+    async fn send_keepalive(&mut self) -> Result<(), ExchangeConnectionError> {
+        self.write_stream
+            .send(Message::Ping(vec![]))
+            .await
+            .map_err(err_str!(ExchangeConnectionError::ConnectionHangup))
+    }
+
+    async fn supports_pair(
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Result<bool, ExchangeConnectionError>
+    where
+        Self: Sized,
+    {
+        let symbol = match Self::symbol(base_token, quote_token) {
+            Ok(symbol) => symbol,
+            Err(_) => return Ok(false),
+        };
+
+        let resp = reqwest::Client::new()
+            .get(BINANCE_EXCHANGE_INFO_URL)
+            .query(&[("symbol", symbol)])
+            .send()
+            .await
+            .map_err(err_str!(ExchangeConnectionError::ConnectionHangup))?;
+
+        // Binance responds with a 400 for unknown symbols
+        if resp.status() == StatusCode::BAD_REQUEST {
+            return Ok(false);
+        }
+
+        let info: ExchangeInfoResponse = resp
+            .error_for_status()
+            .map_err(err_str!(ExchangeConnectionError::ConnectionHangup))?
+            .json()
+            .await
+            .map_err(err_str!(ExchangeConnectionError::InvalidMessage))?;
+
+        Ok(info.symbols.iter().any(|s| s.status == BINANCE_TRADING_STATUS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests building a symbol from a pair of Binance tickers
+    #[test]
+    fn test_symbol_from_tickers() {
+        assert_eq!(BinanceConnection::symbol_from_tickers("ETH", "USDT"), "ETHUSDT");
+        assert_eq!(BinanceConnection::symbol_from_tickers("btc", "usdt"), "BTCUSDT");
+    }
+
+    /// Tests that a symbol cannot be built for tokens without a Binance ticker
+    #[test]
+    fn test_symbol_unknown_token() {
+        let base = Token::from_addr("0x0000000000000000000000000000000000000001");
+        let quote = Token::from_addr("0x0000000000000000000000000000000000000002");
+        assert!(BinanceConnection::symbol(&base, &quote).is_err());
+    }
+
+    /// Tests computing the midpoint from a book ticker update
+    #[test]
+    fn test_midpoint_from_book_ticker() {
+        let msg =
+            r#"{"u":400900217,"s":"ETHUSDT","b":"3000.10","B":"31.21","a":"3000.30","A":"40.66"}"#;
+        let price = BinanceConnection::midpoint_from_ws_message(Message::Text(msg.to_string()))
+            .unwrap()
+            .unwrap();
+        assert!((price - 3000.2).abs() < 1e-9);
+    }
+
+    /// Tests that messages without a book update carry no price
+    #[test]
+    fn test_midpoint_from_control_frame() {
+        let res = BinanceConnection::midpoint_from_ws_message(Message::Ping(vec![])).unwrap();
+        assert!(res.is_none());
+    }
+
+    /// Tests that a malformed book ticker update is rejected
+    #[test]
+    fn test_midpoint_from_invalid_message() {
+        let msg = Message::Text(r#"{"b":"not a price","a":"3000.30"}"#.to_string());
+        assert!(BinanceConnection::midpoint_from_ws_message(msg).is_err());
+    }
+}
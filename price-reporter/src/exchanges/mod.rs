@@ -0,0 +1,46 @@
+//! Exchange connections implemented locally in the price reporter
+//!
+//! Exchanges not implemented here are delegated to the Renegade price reporter
+//! library
+
+use renegade_common::types::{exchange::Exchange, token::Token};
+use renegade_price_reporter::{
+    errors::ExchangeConnectionError,
+    exchange::{
+        connect_exchange as connect_library_exchange, supports_pair as library_supports_pair,
+        ExchangeConnection,
+    },
+    worker::ExchangeConnectionsConfig,
+};
+
+use self::binance::BinanceConnection;
+
+pub mod binance;
+
+/// Connect to the given pair on the given exchange
+pub async fn connect_exchange(
+    base_token: &Token,
+    quote_token: &Token,
+    config: &ExchangeConnectionsConfig,
+    exchange: Exchange,
+) -> Result<Box<dyn ExchangeConnection>, ExchangeConnectionError> {
+    let base = base_token.clone();
+    let quote = quote_token.clone();
+
+    Ok(match exchange {
+        Exchange::Binance => Box::new(BinanceConnection::connect(base, quote, config).await?),
+        _ => connect_library_exchange(base_token, quote_token, config, exchange).await?,
+    })
+}
+
+/// Check whether the given exchange supports the given pair
+pub async fn supports_pair(
+    exchange: &Exchange,
+    base_token: &Token,
+    quote_token: &Token,
+) -> Result<bool, ExchangeConnectionError> {
+    match exchange {
+        Exchange::Binance => BinanceConnection::supports_pair(base_token, quote_token).await,
+        _ => library_supports_pair(exchange, base_token, quote_token).await,
+    }
+}
@@ -9,6 +9,7 @@ use hyper::{
 };
 use matchit::Router;
 use routes::{RefreshTokenMappingHandler, REFRESH_TOKEN_MAPPING_ROUTE};
+use tokio::sync::oneshot::Receiver as OneshotReceiver;
 
 use crate::{
     errors::ServerError,
@@ -61,6 +62,7 @@ impl HttpServer {
                     config.remap_chain,
                     price_streams,
                     config.exchange_conn_config.clone(),
+                    config.binance_enabled,
                 )),
             )
             .unwrap();
@@ -82,7 +84,10 @@ impl HttpServer {
 
     /// The execution loop for the http server, accepts incoming connections,
     /// serves them, and awaits the next connection
-    pub async fn execution_loop(self) -> Result<(), ServerError> {
+    ///
+    /// Once a shutdown signal is received, the server stops accepting new
+    /// connections and returns after in-flight requests complete
+    pub async fn execution_loop(self, shutdown_rx: OneshotReceiver<()>) -> Result<(), ServerError> {
         // Build an HTTP handler callback
         // Clone self and move it into each layer of the callback so that each
         // scope has its own copy of self
@@ -101,6 +106,10 @@ impl HttpServer {
         let addr: SocketAddr = format!("0.0.0.0:{}", self.port).parse().unwrap();
         Server::bind(&addr)
             .serve(make_service)
+            .with_graceful_shutdown(async {
+                // A dropped sender is treated as a shutdown signal
+                let _ = shutdown_rx.await;
+            })
             .await
             .map_err(|err| ServerError::HttpServer(err.to_string()))
     }
@@ -12,7 +12,7 @@ use renegade_util::err_str;
 use crate::{
     errors::ServerError,
     init_default_price_streams,
-    utils::{parse_pair_info_from_topic, UrlParams},
+    utils::{parse_median_topic, UrlParams},
     ws_server::GlobalPriceStreams,
 };
 
@@ -72,16 +72,22 @@ impl PriceHandler {
     }
 
     /// Get a single price from the stream pertaining to the given topic
+    ///
+    /// Errors if the topic is a median stream that has not yet computed a
+    /// price
     pub async fn get_price(&self, topic: &str) -> Result<Price, ServerError> {
         let self_clone = self.clone();
 
-        let pair_info = parse_pair_info_from_topic(topic)?;
-        let price_rx = self_clone
+        let (topic, price_rx) = self_clone
             .price_streams
-            .get_or_create_price_stream(pair_info, self_clone.config.clone())
+            .get_or_create_topic_stream(topic, self_clone.config.clone())
             .await?;
 
         let price = *price_rx.borrow();
+        if parse_median_topic(&topic).is_some() && price == Price::default() {
+            return Err(ServerError::PriceUnavailable(format!("no median price yet for {topic}")));
+        }
+
         Ok(price)
     }
 }
@@ -124,6 +130,8 @@ pub struct RefreshTokenMappingHandler {
     price_streams: GlobalPriceStreams,
     /// The configuration for the exchange connections
     config: ExchangeConnectionsConfig,
+    /// Whether or not the Binance connection is enabled
+    binance_enabled: bool,
 }
 
 impl RefreshTokenMappingHandler {
@@ -134,8 +142,9 @@ impl RefreshTokenMappingHandler {
         remap_chain: Chain,
         price_streams: GlobalPriceStreams,
         config: ExchangeConnectionsConfig,
+        binance_enabled: bool,
     ) -> Self {
-        Self { admin_key, token_remap_path, remap_chain, price_streams, config }
+        Self { admin_key, token_remap_path, remap_chain, price_streams, config, binance_enabled }
     }
 
     /// Authenticate a token mapping refresh request using the admin HMAC key.
@@ -161,7 +170,7 @@ impl RefreshTokenMappingHandler {
             .and_then(|res| res.map_err(err_str!(ServerError::TokenRemap)))?;
 
         // Re-initialize the default price streams after refreshing the token mapping
-        init_default_price_streams(&self.price_streams, &self.config)
+        init_default_price_streams(&self.price_streams, &self.config, self.binance_enabled)
     }
 }
 
@@ -20,12 +20,17 @@ use renegade_common::types::{
 use renegade_config::setup_token_remaps;
 use renegade_price_reporter::worker::ExchangeConnectionsConfig;
 use renegade_util::err_str;
-use tokio::{net::TcpListener, sync::mpsc::unbounded_channel};
-use tracing::{error, info};
-use utils::{parse_config_env_vars, setup_logging};
+use tokio::{
+    net::TcpListener,
+    signal::unix::{signal, SignalKind},
+    sync::{mpsc::unbounded_channel, oneshot},
+};
+use tracing::{error, info, warn};
+use utils::{parse_config_env_vars, parse_pair_info_from_topic, setup_logging};
 use ws_server::{handle_connection, GlobalPriceStreams};
 
 mod errors;
+mod exchanges;
 mod http_server;
 mod utils;
 mod ws_server;
@@ -36,7 +41,7 @@ async fn main() -> Result<(), ServerError> {
     setup_logging();
 
     // Parse configuration env vars
-    let price_reporter_config = parse_config_env_vars();
+    let price_reporter_config = parse_config_env_vars()?;
 
     // Set up the token remapping
     let token_remap_path = price_reporter_config.token_remap_path.clone();
@@ -48,8 +53,17 @@ async fn main() -> Result<(), ServerError> {
     .unwrap()?;
 
     let (closure_tx, mut closure_rx) = unbounded_channel();
-    let global_price_streams = GlobalPriceStreams::new(closure_tx);
-    init_default_price_streams(&global_price_streams, &price_reporter_config.exchange_conn_config)?;
+    let global_price_streams = GlobalPriceStreams::new(
+        closure_tx,
+        price_reporter_config.staleness_config.clone(),
+        price_reporter_config.median_outlier_threshold_pct,
+        price_reporter_config.binance_enabled,
+    );
+    init_default_price_streams(
+        &global_price_streams,
+        &price_reporter_config.exchange_conn_config,
+        price_reporter_config.binance_enabled,
+    )?;
 
     // Bind the server to the given port
     let addr: SocketAddr = format!("0.0.0.0:{:?}", price_reporter_config.ws_port).parse().unwrap();
@@ -60,10 +74,13 @@ async fn main() -> Result<(), ServerError> {
     info!("Listening on: {}", addr);
 
     let http_server = HttpServer::new(&price_reporter_config, global_price_streams.clone());
-    tokio::spawn(http_server.execution_loop());
-    // TODO: Handle shutdown of the HTTP server
+    let (http_shutdown_tx, http_shutdown_rx) = oneshot::channel();
+    let http_handle = tokio::spawn(http_server.execution_loop(http_shutdown_rx));
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
 
-    loop {
+    let res = loop {
         tokio::select! {
             // Handle incoming connections
             Ok((stream, _)) = listener.accept() => {
@@ -75,12 +92,51 @@ async fn main() -> Result<(), ServerError> {
             }
             // Handle price stream closure
             Some(res) = closure_rx.recv() => {
-                if let Err(e) = res {
-                    error!("Shutting down server due to error: {}", e);
-                    break Ok(());
+                match res {
+                    // Stale streams are torn down individually. Default streams are
+                    // re-initialized immediately, others on the next subscription
+                    Err(ServerError::StalePriceStream(topic)) => {
+                        warn!("Tore down stale price stream: {topic}");
+                        reinit_default_price_stream(
+                            &topic,
+                            &global_price_streams,
+                            &price_reporter_config.exchange_conn_config,
+                            price_reporter_config.binance_enabled,
+                        )?;
+                    },
+                    Err(e) => {
+                        error!("Shutting down server due to error: {}", e);
+                        break Ok(());
+                    },
+                    Ok(()) => {},
                 }
             }
+            // Handle a termination signal from the host
+            _ = &mut shutdown => {
+                info!("Received shutdown signal, shutting down server");
+                break Ok(());
+            }
         }
+    };
+
+    // Drain in-flight HTTP requests before exiting
+    let _ = http_shutdown_tx.send(());
+    match http_handle.await {
+        Ok(Err(e)) => error!("HTTP server exited with error: {e}"),
+        Err(e) => error!("HTTP server task failed: {e}"),
+        Ok(Ok(())) => info!("HTTP server shut down"),
+    }
+
+    res
+}
+
+/// Resolves when the process receives a SIGTERM or SIGINT
+async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    let mut sigint = signal(SignalKind::interrupt()).expect("failed to install SIGINT handler");
+    tokio::select! {
+        _ = sigterm.recv() => {},
+        _ = sigint.recv() => {},
     }
 }
 
@@ -88,6 +144,7 @@ async fn main() -> Result<(), ServerError> {
 pub fn init_default_price_streams(
     global_price_streams: &GlobalPriceStreams,
     config: &ExchangeConnectionsConfig,
+    binance_enabled: bool,
 ) -> Result<(), ServerError> {
     info!("Initializing default price streams");
 
@@ -100,7 +157,7 @@ pub fn init_default_price_streams(
         }
 
         let base_token = Token::from_addr(addr);
-        let supported_exchanges = get_supported_exchanges(&base_token, config);
+        let supported_exchanges = get_supported_exchanges(&base_token, config, binance_enabled);
         for exchange in supported_exchanges.into_iter() {
             let quote_token = default_exchange_stable(&exchange);
             // We assume that the exchange has a market between the base token
@@ -118,6 +175,32 @@ pub fn init_default_price_streams(
     Ok(())
 }
 
+/// Re-initialize a torn down price stream if it is one of the default streams
+/// initialized at startup
+fn reinit_default_price_stream(
+    topic: &str,
+    global_price_streams: &GlobalPriceStreams,
+    config: &ExchangeConnectionsConfig,
+    binance_enabled: bool,
+) -> Result<(), ServerError> {
+    let (exchange, base_token, quote_token) = parse_pair_info_from_topic(topic)?;
+    let is_default = match base_token.get_ticker() {
+        Some(ticker) => {
+            ![USD_TICKER, USDC_TICKER, USDT_TICKER].contains(&ticker.as_str())
+                && quote_token == default_exchange_stable(&exchange)
+                && get_supported_exchanges(&base_token, config, binance_enabled).contains(&exchange)
+        },
+        None => false,
+    };
+
+    if !is_default {
+        return Ok(());
+    }
+
+    info!("Re-initializing default price stream: {topic}");
+    init_price_stream(base_token, quote_token, exchange, global_price_streams, config.clone())
+}
+
 /// Spawn a task to initialize a price stream for a given token pair
 #[allow(clippy::needless_pass_by_value)]
 fn init_price_stream(
@@ -144,6 +227,7 @@ fn init_price_stream(
 fn get_supported_exchanges(
     base_token: &Token,
     config: &ExchangeConnectionsConfig,
+    binance_enabled: bool,
 ) -> HashSet<Exchange> {
     let mut supported_exchanges = base_token.supported_exchanges();
     if config.coinbase_api_key.is_none() || config.coinbase_api_secret.is_none() {
@@ -154,5 +238,9 @@ fn get_supported_exchanges(
         supported_exchanges.remove(&Exchange::UniswapV3);
     }
 
+    if !binance_enabled {
+        supported_exchanges.remove(&Exchange::Binance);
+    }
+
     supported_exchanges
 }
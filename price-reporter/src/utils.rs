@@ -1,18 +1,24 @@
 //! Miscellaneous utility types and helper functions.
 
-use std::{collections::HashMap, env, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap, env, fmt::Display, pin::Pin, str::FromStr, sync::Arc, time::Duration,
+};
 
-use futures_util::stream::SplitSink;
+use futures_util::{
+    stream::{self, SplitSink},
+    Stream, StreamExt,
+};
 use matchit::Router;
 use renegade_arbitrum_client::constants::Chain;
 use renegade_common::types::{exchange::Exchange, token::Token, wallet::keychain::HmacKey, Price};
-use renegade_price_reporter::{exchange::supports_pair, worker::ExchangeConnectionsConfig};
+use renegade_price_reporter::worker::ExchangeConnectionsConfig;
 use renegade_util::err_str;
 use serde::{Deserialize, Serialize};
 use tokio::{
     net::TcpStream,
     sync::watch::{Receiver as WatchReceiver, Sender as WatchSender},
     sync::{mpsc::UnboundedSender, RwLock},
+    time::Instant,
 };
 use tokio_stream::{wrappers::WatchStream, StreamMap};
 use tokio_tungstenite::WebSocketStream;
@@ -24,7 +30,7 @@ use tracing_subscriber::{
 };
 use tungstenite::Message;
 
-use crate::{errors::ServerError, http_server::routes::Handler};
+use crate::{errors::ServerError, exchanges::supports_pair, http_server::routes::Handler};
 
 // ----------
 // | CONSTS |
@@ -40,6 +46,27 @@ pub const CONN_RETRY_DELAY_MS: u64 = 2_000; // 2 seconds
 pub const MAX_CONN_RETRY_WINDOW_MS: u64 = 60_000; // 1 minute
 /// The maximum number of retries to attempt before giving up on a connection
 pub const MAX_CONN_RETRIES: usize = 5;
+/// The prefix of topics for median-aggregated price streams, e.g.
+/// `Median-<mint>`
+pub const MEDIAN_TOPIC_PREFIX: &str = "Median";
+/// The maximum number of topics that may be subscribed to in a single batched
+/// subscription message
+pub const MAX_BATCH_TOPICS: usize = 100;
+/// The number of milliseconds to wait in between re-scanning the constituent
+/// exchange streams of a median price stream
+pub const MEDIAN_CONSTITUENT_REFRESH_MS: u64 = 5_000; // 5 seconds
+/// The number of milliseconds for which a median price stream is kept alive
+/// after it was last fetched, even if it has no subscribers
+///
+/// This keeps streams polled over HTTP, which do not hold a subscription,
+/// from being torn down between reads
+pub const MEDIAN_STREAM_IDLE_TIMEOUT_MS: u64 = 60_000; // 1 minute
+/// The default percentage by which an exchange's price may deviate from the
+/// median before it is excluded as an outlier
+const DEFAULT_MEDIAN_OUTLIER_THRESHOLD_PCT: f64 = 5.0;
+/// The exchanges for which a staleness threshold may be configured
+const STALENESS_CONFIGURABLE_EXCHANGES: [Exchange; 5] =
+    [Exchange::Binance, Exchange::Coinbase, Exchange::Kraken, Exchange::Okx, Exchange::UniswapV3];
 
 /// The name of the environment variable specifying the port on which the
 /// server listens for incoming websocket connections
@@ -69,6 +96,18 @@ const CB_API_SECRET_ENV_VAR: &str = "CB_API_SECRET";
 /// The name of the environment variable specifying the Ethereum
 /// RPC node websocket address
 const ETH_WS_ADDR_ENV_VAR: &str = "ETH_WS_ADDR";
+/// The name of the environment variable specifying the staleness threshold in
+/// milliseconds for exchanges without their own threshold
+const DEFAULT_STALENESS_MS_ENV_VAR: &str = "DEFAULT_STALENESS_MS";
+/// The suffix of the environment variables specifying the per-exchange
+/// staleness threshold in milliseconds, e.g. `COINBASE_STALENESS_MS`
+const STALENESS_MS_ENV_VAR_SUFFIX: &str = "_STALENESS_MS";
+/// The name of the environment variable specifying the percentage by which an
+/// exchange's price may deviate from the median before it is excluded
+const MEDIAN_OUTLIER_THRESHOLD_PCT_ENV_VAR: &str = "MEDIAN_OUTLIER_THRESHOLD_PCT";
+/// The name of the environment variable toggling the Binance connection,
+/// enabled by default
+const ENABLE_BINANCE_ENV_VAR: &str = "ENABLE_BINANCE";
 /// The name of the environment variable specifying the HMAC key for the admin
 /// API
 const ADMIN_KEY_ENV_VAR: &str = "ADMIN_KEY";
@@ -93,9 +132,25 @@ pub type SharedPriceStreams = Arc<RwLock<HashMap<PairInfo, PriceReceiver>>>;
 /// A type alias for a price stream
 pub type PriceStream = WatchStream<Price>;
 
-/// A type alias for a mapped stream prices, indexed by the (source, base,
-/// quote) tuple
-pub type PriceStreamMap = StreamMap<PairInfo, PriceStream>;
+/// A type alias for a shareable map of median price streams, indexed by the
+/// base token
+pub type SharedMedianStreams = Arc<RwLock<HashMap<Token, MedianStream>>>;
+
+/// A median price stream, along with the time it was last fetched
+#[derive(Clone)]
+pub struct MedianStream {
+    /// The receiver end of the median price stream
+    pub price_rx: PriceReceiver,
+    /// The time at which the stream was last fetched
+    pub last_fetched: Instant,
+}
+
+/// A type alias for a client's subscription to a price stream, which yields
+/// `None` once the underlying stream is torn down
+pub type SubscriptionStream = Pin<Box<dyn Stream<Item = Option<Price>> + Send>>;
+
+/// A type alias for a mapped stream prices, indexed by topic
+pub type PriceStreamMap = StreamMap<String, SubscriptionStream>;
 
 /// A type alias for a websocket write stream
 pub type WsWriteStream = SplitSink<WebSocketStream<TcpStream>, Message>;
@@ -119,6 +174,70 @@ pub struct PriceMessage {
     pub price: Price,
 }
 
+/// A message that is sent by the price reporter to the client indicating that
+/// the price stream for the given topic was torn down
+///
+/// The client is unsubscribed from the topic, and may resubscribe to
+/// re-initialize the stream
+#[derive(Serialize, Deserialize)]
+pub struct PriceStreamClosedMessage {
+    /// The topic whose price stream was torn down
+    pub topic: String,
+    /// The reason the stream was torn down
+    pub error: String,
+}
+
+/// A message sent by the client to subscribe to multiple topics at once
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+pub enum BatchWebsocketMessage {
+    /// Subscribe to every valid topic in the batch
+    SubscribeBatch {
+        /// The topics to subscribe to
+        topics: Vec<String>,
+    },
+}
+
+/// The response to a batched subscription message
+#[derive(Serialize, Deserialize)]
+pub struct BatchSubscriptionResponse {
+    /// The normalized topics that were subscribed to by the batch
+    pub subscribed: Vec<String>,
+    /// The topics in the batch that could not be subscribed to
+    pub invalid: Vec<InvalidTopic>,
+    /// All topics the client is subscribed to after the batch was applied
+    pub subscriptions: Vec<String>,
+}
+
+/// A topic in a batched subscription that could not be subscribed to
+#[derive(Serialize, Deserialize)]
+pub struct InvalidTopic {
+    /// The topic as given by the client
+    pub topic: String,
+    /// The reason the subscription failed
+    pub error: String,
+}
+
+/// The staleness thresholds for price streams, after which a stream that has
+/// not received an update is torn down
+///
+/// Staleness detection is opt-in, streams of exchanges without a threshold are
+/// never torn down for staleness
+#[derive(Clone, Debug, Default)]
+pub struct StalenessConfig {
+    /// The threshold used for exchanges without an explicit threshold
+    pub default_threshold: Option<Duration>,
+    /// The per-exchange thresholds
+    pub exchange_thresholds: HashMap<Exchange, Duration>,
+}
+
+impl StalenessConfig {
+    /// Get the staleness threshold for the given exchange, if one is set
+    pub fn threshold(&self, exchange: &Exchange) -> Option<Duration> {
+        self.exchange_thresholds.get(exchange).copied().or(self.default_threshold)
+    }
+}
+
 /// The configuration options for the price reporter server
 pub struct PriceReporterConfig {
     /// The port on which the server listens for incoming websocket connections
@@ -131,6 +250,13 @@ pub struct PriceReporterConfig {
     pub remap_chain: Chain,
     /// The configuration options that may be used by exchange connections
     pub exchange_conn_config: ExchangeConnectionsConfig,
+    /// Whether or not the Binance connection is enabled
+    pub binance_enabled: bool,
+    /// The staleness thresholds for the price streams
+    pub staleness_config: StalenessConfig,
+    /// The percentage by which an exchange's price may deviate from the
+    /// median before it is excluded from the median price stream
+    pub median_outlier_threshold_pct: f64,
     /// The HMAC key for the admin API. If one is not provided, the admin API
     /// will be disabled.
     pub admin_key: Option<HmacKey>,
@@ -151,7 +277,7 @@ pub fn setup_logging() {
 }
 
 /// Parse the configuration options from environment variables
-pub fn parse_config_env_vars() -> PriceReporterConfig {
+pub fn parse_config_env_vars() -> Result<PriceReporterConfig, ServerError> {
     let ws_port = env::var(WS_PORT_ENV_VAR).map(|p| p.parse().unwrap()).unwrap_or(DEFAULT_WS_PORT);
     let http_port =
         env::var(HTTP_PORT_ENV_VAR).map(|p| p.parse().unwrap()).unwrap_or(DEFAULT_HTTP_PORT);
@@ -161,11 +287,15 @@ pub fn parse_config_env_vars() -> PriceReporterConfig {
     let coinbase_api_key = env::var(CB_API_KEY_ENV_VAR).ok();
     let coinbase_api_secret = env::var(CB_API_SECRET_ENV_VAR).ok();
     let eth_websocket_addr = env::var(ETH_WS_ADDR_ENV_VAR).ok();
+    let binance_enabled = parse_env_var(ENABLE_BINANCE_ENV_VAR)?.unwrap_or(true);
+    let median_outlier_threshold_pct = env::var(MEDIAN_OUTLIER_THRESHOLD_PCT_ENV_VAR)
+        .map(|p| p.parse().unwrap())
+        .unwrap_or(DEFAULT_MEDIAN_OUTLIER_THRESHOLD_PCT);
     let admin_key = env::var(ADMIN_KEY_ENV_VAR)
         .ok()
         .map(|key_str| HmacKey::from_base64_string(&key_str).expect("Invalid admin HMAC key"));
 
-    PriceReporterConfig {
+    Ok(PriceReporterConfig {
         ws_port,
         http_port,
         token_remap_path,
@@ -175,10 +305,51 @@ pub fn parse_config_env_vars() -> PriceReporterConfig {
             coinbase_api_secret,
             eth_websocket_addr,
         },
+        binance_enabled,
+        staleness_config: parse_staleness_config()?,
+        median_outlier_threshold_pct,
         admin_key,
+    })
+}
+
+/// Parse the per-exchange staleness thresholds from environment variables
+fn parse_staleness_config() -> Result<StalenessConfig, ServerError> {
+    let default_threshold =
+        parse_env_var::<u64>(DEFAULT_STALENESS_MS_ENV_VAR)?.map(Duration::from_millis);
+
+    let mut exchange_thresholds = HashMap::new();
+    for exchange in STALENESS_CONFIGURABLE_EXCHANGES {
+        let env_var =
+            format!("{}{STALENESS_MS_ENV_VAR_SUFFIX}", exchange.to_string().to_uppercase());
+        if let Some(ms) = parse_env_var::<u64>(&env_var)? {
+            exchange_thresholds.insert(exchange, Duration::from_millis(ms));
+        }
+    }
+
+    Ok(StalenessConfig { default_threshold, exchange_thresholds })
+}
+
+/// Parse an optional environment variable, erroring with the variable's name
+/// if it is set to an invalid value
+fn parse_env_var<T>(name: &str) -> Result<Option<T>, ServerError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|e| ServerError::InvalidConfig(format!("invalid {name} `{value}`: {e}"))),
+        Err(_) => Ok(None),
     }
 }
 
+/// Get a client subscription to the given price stream
+pub fn new_subscription_stream(price_rx: PriceReceiver) -> SubscriptionStream {
+    Box::pin(PriceStream::new(price_rx).map(Some).chain(stream::once(async { None })))
+}
+
 /// Get the topic name for a given pair info
 pub fn get_pair_info_topic(pair_info: &PairInfo) -> String {
     format!("{}-{}-{}", pair_info.0, pair_info.1, pair_info.2)
@@ -194,20 +365,93 @@ pub fn parse_pair_info_from_topic(topic: &str) -> Result<PairInfo, ServerError>
     Ok((exchange, base, quote))
 }
 
+/// Get the median price stream topic for a given base token
+pub fn get_median_topic(base: &Token) -> String {
+    format!("{MEDIAN_TOPIC_PREFIX}-{base}")
+}
+
+/// Parse the base token from a median price stream topic, returning `None` if
+/// the topic is not a median topic
+pub fn parse_median_topic(topic: &str) -> Option<Token> {
+    let (prefix, mint) = topic.split_once('-')?;
+    (prefix == MEDIAN_TOPIC_PREFIX).then(|| Token::from_addr(mint))
+}
+
+/// Normalize a topic into the form under which its stream is indexed
+pub fn normalize_topic(topic: &str) -> Result<String, ServerError> {
+    match parse_median_topic(topic) {
+        Some(base) => Ok(get_median_topic(&base)),
+        None => parse_pair_info_from_topic(topic).map(|pair_info| get_pair_info_topic(&pair_info)),
+    }
+}
+
 /// Get all the topics that are subscribed to in a `PriceStreamMap`
 pub fn get_subscribed_topics(subscriptions: &PriceStreamMap) -> Vec<String> {
-    subscriptions.keys().map(get_pair_info_topic).collect()
+    subscriptions.keys().cloned().collect()
+}
+
+/// Compute the median of the given prices after excluding those that deviate
+/// from it by more than `outlier_threshold_pct` percent
+pub fn median_excluding_outliers(prices: &[Price], outlier_threshold_pct: f64) -> Option<Price> {
+    let initial_median = median(prices)?;
+    let inliers: Vec<Price> = prices
+        .iter()
+        .copied()
+        .filter(|price| {
+            (price - initial_median).abs() / initial_median * 100.0 <= outlier_threshold_pct
+        })
+        .collect();
+
+    median(&inliers)
 }
 
-/// Validate a pair info tuple, checking that the exchange supports the base
-/// and quote tokens
-pub async fn validate_subscription(pair_info: &PairInfo) -> Result<(), ServerError> {
+/// Compute the median of the given prices
+fn median(prices: &[Price]) -> Option<Price> {
+    if prices.is_empty() {
+        return None;
+    }
+
+    let mut sorted = prices.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Validate the base token of a median topic, checking that it is a known
+/// token listed on at least one exchange
+pub fn validate_median_subscription(base: &Token) -> Result<(), ServerError> {
+    if base.get_ticker().is_none() {
+        return Err(ServerError::InvalidPairInfo(format!("unknown token {base}")));
+    }
+
+    if base.supported_exchanges().is_empty() {
+        return Err(ServerError::InvalidPairInfo(format!("{base} is not listed on any exchange")));
+    }
+
+    Ok(())
+}
+
+/// Validate a pair info tuple, checking that the exchange is enabled and
+/// supports the base and quote tokens
+pub async fn validate_subscription(
+    pair_info: &PairInfo,
+    binance_enabled: bool,
+) -> Result<(), ServerError> {
     let (exchange, base, quote) = pair_info;
 
     if exchange == &Exchange::UniswapV3 {
         return Err(ServerError::InvalidPairInfo("UniswapV3 is not supported".to_string()));
     }
 
+    if exchange == &Exchange::Binance && !binance_enabled {
+        return Err(ServerError::InvalidPairInfo("Binance is disabled".to_string()));
+    }
+
     if !supports_pair(exchange, base, quote).await.map_err(ServerError::ExchangeConnection)? {
         return Err(ServerError::InvalidPairInfo(format!(
             "{} does not support the pair ({}, {})",
@@ -217,3 +461,37 @@ pub async fn validate_subscription(pair_info: &PairInfo) -> Result<(), ServerErr
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::median_excluding_outliers;
+
+    /// The outlier threshold used in tests
+    const THRESHOLD_PCT: f64 = 5.0;
+
+    /// Tests that the median of no prices is undefined
+    #[test]
+    fn test_median_empty() {
+        assert_eq!(median_excluding_outliers(&[], THRESHOLD_PCT), None);
+    }
+
+    /// Tests that a single price is its own median
+    #[test]
+    fn test_median_single_price() {
+        assert_eq!(median_excluding_outliers(&[100.0], THRESHOLD_PCT), Some(100.0));
+    }
+
+    /// Tests that an outlier is excluded before the final median is taken
+    #[test]
+    fn test_median_excludes_outlier() {
+        let prices = [100.0, 101.0, 99.0, 150.0];
+        assert_eq!(median_excluding_outliers(&prices, THRESHOLD_PCT), Some(100.0));
+    }
+
+    /// Tests that no median is produced when every price is an outlier
+    #[test]
+    fn test_median_all_outliers() {
+        let prices = [100.0, 200.0];
+        assert_eq!(median_excluding_outliers(&prices, THRESHOLD_PCT), None);
+    }
+}
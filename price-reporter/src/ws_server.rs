@@ -5,10 +5,13 @@ use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
 
 use futures_util::{SinkExt, StreamExt};
 use renegade_api::websocket::{SubscriptionResponse, WebsocketMessage};
-use renegade_common::types::Price;
+use renegade_common::types::{
+    exchange::Exchange,
+    token::{default_exchange_stable, Token},
+    Price,
+};
 use renegade_price_reporter::{
-    errors::ExchangeConnectionError,
-    exchange::{connect_exchange, ExchangeConnection},
+    errors::ExchangeConnectionError, exchange::ExchangeConnection,
     worker::ExchangeConnectionsConfig,
 };
 use renegade_util::err_str;
@@ -20,11 +23,16 @@ use tungstenite::Message;
 
 use crate::{
     errors::ServerError,
+    exchanges::connect_exchange,
     utils::{
-        get_pair_info_topic, get_subscribed_topics, parse_pair_info_from_topic,
-        validate_subscription, ClosureSender, PairInfo, PriceMessage, PriceReceiver, PriceSender,
-        PriceStream, PriceStreamMap, SharedPriceStreams, WsWriteStream, CONN_RETRY_DELAY_MS,
-        KEEPALIVE_INTERVAL_MS, MAX_CONN_RETRIES, MAX_CONN_RETRY_WINDOW_MS,
+        get_median_topic, get_pair_info_topic, get_subscribed_topics, median_excluding_outliers,
+        new_subscription_stream, normalize_topic, parse_median_topic, parse_pair_info_from_topic,
+        validate_median_subscription, validate_subscription, BatchSubscriptionResponse,
+        BatchWebsocketMessage, ClosureSender, InvalidTopic, MedianStream, PairInfo, PriceMessage,
+        PriceReceiver, PriceSender, PriceStream, PriceStreamClosedMessage, PriceStreamMap,
+        SharedMedianStreams, SharedPriceStreams, StalenessConfig, WsWriteStream,
+        CONN_RETRY_DELAY_MS, KEEPALIVE_INTERVAL_MS, MAX_BATCH_TOPICS, MAX_CONN_RETRIES,
+        MAX_CONN_RETRY_WINDOW_MS, MEDIAN_CONSTITUENT_REFRESH_MS, MEDIAN_STREAM_IDLE_TIMEOUT_MS,
     },
 };
 
@@ -41,12 +49,34 @@ pub struct GlobalPriceStreams {
     pub price_streams: SharedPriceStreams,
     /// A channel to send closure signals from the price stream tasks
     pub closure_channel: ClosureSender,
+    /// The staleness thresholds for the price streams
+    pub staleness_config: StalenessConfig,
+    /// A thread-safe map of median price streams aggregated across exchanges,
+    /// indexed by base token
+    pub median_streams: SharedMedianStreams,
+    /// The percentage by which an exchange's price may deviate from the
+    /// median before it is excluded from the median price stream
+    pub median_outlier_threshold_pct: f64,
+    /// Whether or not the Binance connection is enabled
+    pub binance_enabled: bool,
 }
 
 impl GlobalPriceStreams {
     /// Instantiate a new global price streams map
-    pub fn new(closure_channel: ClosureSender) -> Self {
-        Self { price_streams: Arc::new(RwLock::new(HashMap::new())), closure_channel }
+    pub fn new(
+        closure_channel: ClosureSender,
+        staleness_config: StalenessConfig,
+        median_outlier_threshold_pct: f64,
+        binance_enabled: bool,
+    ) -> Self {
+        Self {
+            price_streams: Arc::new(RwLock::new(HashMap::new())),
+            closure_channel,
+            staleness_config,
+            median_streams: Arc::new(RwLock::new(HashMap::new())),
+            median_outlier_threshold_pct,
+            binance_enabled,
+        }
     }
 
     /// Add a price stream to the global map
@@ -65,7 +95,7 @@ impl GlobalPriceStreams {
         pair_info: PairInfo,
         config: ExchangeConnectionsConfig,
     ) -> Result<PriceReceiver, ServerError> {
-        validate_subscription(&pair_info).await?;
+        validate_subscription(&pair_info, self.binance_enabled).await?;
 
         info!("Initializing price stream for {}", get_pair_info_topic(&pair_info));
 
@@ -76,8 +106,11 @@ impl GlobalPriceStreams {
         // Spawn a task responsible for forwarding prices into the broadcast channel &
         // sending keepalive messages to the exchange
         let global_price_streams = self.clone();
+        let staleness_threshold = self.staleness_config.threshold(&pair_info.0);
         tokio::spawn(async move {
-            let res = Self::price_stream_task(config, pair_info.clone(), price_tx).await;
+            let res =
+                Self::price_stream_task(config, pair_info.clone(), price_tx, staleness_threshold)
+                    .await;
             global_price_streams.remove_price_stream(pair_info).await;
             global_price_streams.closure_channel.send(res).unwrap()
         });
@@ -87,10 +120,14 @@ impl GlobalPriceStreams {
     }
 
     /// The task responsible for streaming prices from the exchange
+    ///
+    /// Returns an error if the connection cannot be (re-)established or the
+    /// stream goes stale
     async fn price_stream_task(
         config: ExchangeConnectionsConfig,
         pair_info: PairInfo,
         price_tx: PriceSender,
+        staleness_threshold: Option<Duration>,
     ) -> Result<(), ServerError> {
         let mut retry_timestamps = Vec::new();
 
@@ -99,8 +136,13 @@ impl GlobalPriceStreams {
             Self::connect_with_retries(&pair_info, &config, &mut retry_timestamps).await?;
 
         loop {
-            match Self::manage_connection(&mut conn, &price_tx).await {
+            match Self::manage_connection(&mut conn, &price_tx, staleness_threshold).await {
                 Ok(()) => {},
+                // A stale stream is torn down rather than reconnected
+                Err(ServerError::StalePriceStream(_)) => {
+                    let topic = get_pair_info_topic(&pair_info);
+                    return Err(ServerError::StalePriceStream(topic));
+                },
                 Err(e) => {
                     conn = Self::exhaust_retries(e, &pair_info, &config, &mut retry_timestamps)
                         .await?;
@@ -111,12 +153,18 @@ impl GlobalPriceStreams {
 
     /// Manages an exchange connection, sending keepalive messages and
     /// forwarding prices to the price receiver
+    ///
+    /// Errors if no price is received within the staleness threshold, if one
+    /// is set
     async fn manage_connection(
         conn: &mut Box<dyn ExchangeConnection>,
         price_tx: &PriceSender,
+        staleness_threshold: Option<Duration>,
     ) -> Result<(), ServerError> {
         let delay = tokio::time::sleep(Duration::from_millis(KEEPALIVE_INTERVAL_MS));
         tokio::pin!(delay);
+        let staleness_timer = tokio::time::sleep(staleness_threshold.unwrap_or_default());
+        tokio::pin!(staleness_timer);
 
         loop {
             tokio::select! {
@@ -126,10 +174,19 @@ impl GlobalPriceStreams {
                     delay.as_mut().reset(Instant::now() + Duration::from_millis(KEEPALIVE_INTERVAL_MS));
                 }
 
+                // Tear down the stream if it has not received a price in time
+                _ = &mut staleness_timer, if staleness_threshold.is_some() => {
+                    let msg = format!("no price received in {staleness_threshold:?}");
+                    return Err(ServerError::StalePriceStream(msg));
+                }
+
                 // Forward the next price into the broadcast channel
                 Some(price_res) = conn.next() => {
                     let price = price_res.map_err(ServerError::ExchangeConnection)?;
                     let _ = price_tx.send(price);
+                    if let Some(threshold) = staleness_threshold {
+                        staleness_timer.as_mut().reset(Instant::now() + threshold);
+                    }
                 }
             }
         }
@@ -233,6 +290,157 @@ impl GlobalPriceStreams {
 
         Ok(recv)
     }
+
+    /// Fetch the price stream for the given topic from the global maps,
+    /// creating it if necessary
+    ///
+    /// Returns the normalized topic along with the stream
+    pub async fn get_or_create_topic_stream(
+        &self,
+        topic: &str,
+        config: ExchangeConnectionsConfig,
+    ) -> Result<(String, PriceReceiver), ServerError> {
+        if let Some(base) = parse_median_topic(topic) {
+            let topic = get_median_topic(&base);
+            let recv = self.get_or_create_median_stream(base).await?;
+            return Ok((topic, recv));
+        }
+
+        let pair_info = parse_pair_info_from_topic(topic)?;
+        let topic = get_pair_info_topic(&pair_info);
+        let recv = self.get_or_create_price_stream(pair_info, config).await?;
+        Ok((topic, recv))
+    }
+
+    // --- Median Price Streams --- //
+
+    /// Fetch the median price stream for the given base token, creating it if
+    /// necessary
+    pub async fn get_or_create_median_stream(
+        &self,
+        base: Token,
+    ) -> Result<PriceReceiver, ServerError> {
+        let mut median_streams = self.median_streams.write().await;
+        if let Some(stream) = median_streams.get_mut(&base) {
+            stream.last_fetched = Instant::now();
+            return Ok(stream.price_rx.clone());
+        }
+
+        validate_median_subscription(&base)?;
+
+        info!("Initializing median price stream for {}", get_median_topic(&base));
+
+        // Seed the stream with the median of the constituents' current prices
+        let current_prices: Vec<Price> = self
+            .get_constituent_streams(&base)
+            .await
+            .iter()
+            .map(|(_, price_rx)| *price_rx.borrow())
+            .filter(|price| *price > 0.0)
+            .collect();
+        let initial_price =
+            median_excluding_outliers(&current_prices, self.median_outlier_threshold_pct)
+                .unwrap_or_default();
+
+        let (price_tx, price_rx) = channel(initial_price);
+        let stream = MedianStream { price_rx: price_rx.clone(), last_fetched: Instant::now() };
+        median_streams.insert(base.clone(), stream);
+
+        let global_price_streams = self.clone();
+        tokio::spawn(async move { global_price_streams.median_stream_task(base, price_tx).await });
+
+        Ok(price_rx)
+    }
+
+    /// The task responsible for aggregating the prices of the constituent
+    /// exchange streams into a median price
+    ///
+    /// The median is recomputed whenever any constituent stream ticks, and the
+    /// set of constituents is periodically re-scanned so that streams
+    /// connected after the median stream are picked up. The task exits once
+    /// the stream has no subscribers
+    async fn median_stream_task(&self, base: Token, price_tx: PriceSender) {
+        let mut constituents: StreamMap<Exchange, PriceStream> = StreamMap::new();
+        let mut prices: HashMap<Exchange, Price> = HashMap::new();
+        let mut refresh =
+            tokio::time::interval(Duration::from_millis(MEDIAN_CONSTITUENT_REFRESH_MS));
+
+        loop {
+            tokio::select! {
+                // Pick up newly connected constituents and drop closed ones
+                _ = refresh.tick() => {
+                    if self.remove_unused_median_stream(&base, &price_tx).await {
+                        info!("Tearing down unused median price stream for {}", get_median_topic(&base));
+                        return;
+                    }
+
+                    for (exchange, price_rx) in self.get_constituent_streams(&base).await {
+                        if !constituents.contains_key(&exchange) {
+                            constituents.insert(exchange, PriceStream::new(price_rx));
+                        }
+                    }
+                    prices.retain(|exchange, _| constituents.contains_key(exchange));
+                }
+
+                // Recompute the median on each constituent price
+                Some((exchange, price)) = constituents.next() => {
+                    prices.insert(exchange, price);
+
+                    // Skip constituents that have not yet received a price
+                    let valid_prices: Vec<Price> =
+                        prices.values().copied().filter(|price| *price > 0.0).collect();
+                    if let Some(median) =
+                        median_excluding_outliers(&valid_prices, self.median_outlier_threshold_pct)
+                    {
+                        let _ = price_tx.send(median);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove the median stream for the given base token from the global map
+    /// if it has no subscribers and has not been fetched within the idle
+    /// timeout, returning whether it was removed
+    ///
+    /// The stream is checked under the map's write lock, so a subscriber cannot
+    /// fetch the stream as it is removed
+    async fn remove_unused_median_stream(&self, base: &Token, price_tx: &PriceSender) -> bool {
+        let mut median_streams = self.median_streams.write().await;
+
+        // The global map itself holds one receiver
+        if price_tx.receiver_count() > 1 {
+            return false;
+        }
+
+        let idle_timeout = Duration::from_millis(MEDIAN_STREAM_IDLE_TIMEOUT_MS);
+        let recently_fetched =
+            median_streams.get(base).is_some_and(|s| s.last_fetched.elapsed() < idle_timeout);
+        if recently_fetched {
+            return false;
+        }
+
+        median_streams.remove(base);
+        true
+    }
+
+    /// Get the price streams of all exchanges currently connected for the
+    /// given base token, quoted in each exchange's default stablecoin
+    ///
+    /// Pairs quoted in any other token are excluded, as their prices are not
+    /// denominated in USD
+    async fn get_constituent_streams(&self, base: &Token) -> Vec<(Exchange, PriceReceiver)> {
+        let price_streams = self.price_streams.read().await;
+        price_streams
+            .iter()
+            .filter(|((exchange, stream_base, quote), _)| {
+                stream_base == base
+                    && *exchange != Exchange::Renegade
+                    && *quote == default_exchange_stable(exchange)
+            })
+            .map(|((exchange, ..), price_rx)| (*exchange, price_rx.clone()))
+            .collect()
+    }
 }
 
 // ----------
@@ -259,12 +467,17 @@ pub async fn handle_connection(
     loop {
         tokio::select! {
             // Send the next price to the client
-            Some((pair_info, price)) = subscriptions.next() => {
-                // The potential error in `price_res` here is a `BroadcastStreamRecvError::Lagged`,
-                // meaning the stream lagged receiving price updates. We can safely ignore this.
-                let topic = get_pair_info_topic(&pair_info);
-                let message = PriceMessage { topic, price };
-                let message_ser = serde_json::to_string(&message).map_err(err_str!(ServerError::Serde))?;
+            Some((topic, maybe_price)) = subscriptions.next() => {
+                // A subscription yields `None` once its price stream is torn down, after
+                // which it is removed from the map
+                let message_ser = match maybe_price {
+                    Some(price) => serde_json::to_string(&PriceMessage { topic, price }),
+                    None => {
+                        let error = "price stream closed".to_string();
+                        serde_json::to_string(&PriceStreamClosedMessage { topic, error })
+                    },
+                }
+                .map_err(err_str!(ServerError::Serde))?;
                 write_stream
                     .send(Message::Text(message_ser))
                     .await
@@ -315,6 +528,32 @@ async fn handle_ws_message(
     peer_addr: SocketAddr,
 ) -> Result<(), ServerError> {
     if let Message::Text(msg_text) = message {
+        // Batched subscriptions are handled separately, as they succeed with a
+        // per-topic breakdown of the result
+        if let Ok(BatchWebsocketMessage::SubscribeBatch { topics }) =
+            serde_json::from_str(&msg_text)
+        {
+            let response = if topics.len() > MAX_BATCH_TOPICS {
+                format!("Invalid request: batch must contain at most {MAX_BATCH_TOPICS} topics")
+            } else {
+                let res = handle_batch_subscription_message(
+                    topics,
+                    subscriptions,
+                    global_price_streams,
+                    config,
+                    peer_addr,
+                )
+                .await;
+                serde_json::to_string(&res).map_err(err_str!(ServerError::Serde))?
+            };
+            write_stream
+                .send(Message::Text(response))
+                .await
+                .map_err(err_str!(ServerError::WebsocketSend))?;
+
+            return Ok(());
+        }
+
         let msg_deser: Result<WebsocketMessage, _> = serde_json::from_str(&msg_text);
         let resp = match msg_deser {
             // Valid message body
@@ -356,20 +595,57 @@ async fn handle_subscription_message(
 ) -> Result<SubscriptionResponse, ServerError> {
     match message {
         WebsocketMessage::Subscribe { topic } => {
-            let pair_info = parse_pair_info_from_topic(&topic)?;
-
             info!("Subscribing {} to {}", peer_addr, &topic);
 
-            let price_rx =
-                global_price_streams.get_or_create_price_stream(pair_info.clone(), config).await?;
-            subscriptions.insert(pair_info, PriceStream::new(price_rx));
+            let (topic, price_rx) =
+                global_price_streams.get_or_create_topic_stream(&topic, config).await?;
+            subscriptions.insert(topic, new_subscription_stream(price_rx));
         },
         WebsocketMessage::Unsubscribe { topic } => {
             info!("Unsubscribing {} from {}", peer_addr, &topic);
-            let pair_info = parse_pair_info_from_topic(&topic)?;
-            subscriptions.remove(&pair_info);
+            let topic = normalize_topic(&topic)?;
+            subscriptions.remove(&topic);
         },
     };
 
     Ok(SubscriptionResponse { subscriptions: get_subscribed_topics(subscriptions) })
 }
+
+/// Handles an incoming batched subscribe message
+///
+/// All topics are resolved before any subscription is registered, so that the
+/// client's subscriptions are updated in a single step. Topics that fail to
+/// resolve are reported back rather than failing the whole batch
+async fn handle_batch_subscription_message(
+    topics: Vec<String>,
+    subscriptions: &mut PriceStreamMap,
+    global_price_streams: GlobalPriceStreams,
+    config: ExchangeConnectionsConfig,
+    peer_addr: SocketAddr,
+) -> BatchSubscriptionResponse {
+    info!("Subscribing {} to {} topics", peer_addr, topics.len());
+
+    let mut streams = Vec::with_capacity(topics.len());
+    let mut invalid = Vec::new();
+    for topic in topics {
+        match global_price_streams.get_or_create_topic_stream(&topic, config.clone()).await {
+            Ok(stream) => streams.push(stream),
+            Err(e) => {
+                warn!("Invalid topic {} in batch subscription from {}: {}", topic, peer_addr, e);
+                invalid.push(InvalidTopic { topic, error: e.to_string() });
+            },
+        }
+    }
+
+    let mut subscribed = Vec::with_capacity(streams.len());
+    for (topic, price_rx) in streams {
+        subscriptions.insert(topic.clone(), new_subscription_stream(price_rx));
+        subscribed.push(topic);
+    }
+
+    BatchSubscriptionResponse {
+        subscribed,
+        invalid,
+        subscriptions: get_subscribed_topics(subscriptions),
+    }
+}